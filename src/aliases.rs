@@ -0,0 +1,101 @@
+use crate::bot_error::BotResult;
+
+use serde::Deserialize;
+use std::{ fs, path::Path, sync::OnceLock };
+
+const ALIASES_FILE: &str = "stat_aliases.ron";
+
+/// A friendly name players can use instead of a stat name
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatAlias {
+    pub alias: String,
+    /// Replaces the stat type chosen by the player when set, mostly for aliases of derived stats
+    pub stat_type: Option<String>,
+    pub stat_name: String,
+}
+
+// Used along with the aliases of ALIASES_FILE, which take precedence
+const DEFAULT_ALIASES: &[(&str, Option<&str>, &str)] = &[
+    ("diamonds", Some("derived"), "diamonds"),
+    ("diamond", Some("derived"), "diamonds"),
+    ("distance", Some("derived"), "distance travelled"),
+    ("kd", Some("derived"), "kd"),
+    ("k/d", Some("derived"), "kd"),
+    ("playtime", Some("custom"), "play time"),
+    ("time played", Some("custom"), "play time"),
+    ("hours", Some("custom"), "play time"),
+    ("death", Some("custom"), "deaths"),
+    ("jumps", Some("custom"), "jump"),
+    ("mobs killed", Some("custom"), "mob kills"),
+    ("kills", Some("custom"), "mob kills"),
+    ("netherite", Some("mined"), "ancient debris"),
+    ("debris", Some("mined"), "ancient debris"),
+    ("zombies", None, "zombie"),
+    ("skeletons", None, "skeleton"),
+    ("creepers", None, "creeper"),
+    ("spiders", None, "spider"),
+    ("endermen", None, "enderman"),
+    ("endermans", None, "enderman"),
+    ("witches", None, "witch"),
+    ("slimes", None, "slime"),
+    ("phantoms", None, "phantom"),
+    ("blazes", None, "blaze"),
+    ("ghasts", None, "ghast"),
+    ("piglins", None, "piglin"),
+    ("dragon", None, "ender dragon"),
+    ("cows", None, "cow"),
+    ("pigs", None, "pig"),
+    ("sheeps", None, "sheep"),
+    ("chickens", None, "chicken"),
+    ("players", None, "player"),
+];
+
+// Loaded the first time an alias is resolved, changes to ALIASES_FILE are seen after a restart
+static ALIASES: OnceLock<Vec<StatAlias>> = OnceLock::new();
+
+pub fn load_aliases() -> BotResult<Vec<StatAlias>> {
+    load_aliases_from(ALIASES_FILE)
+}
+
+pub fn load_aliases_from(path: &str) -> BotResult<Vec<StatAlias>> {
+    let mut aliases = if Path::new(path).exists() {
+        ron::de::from_str::<Vec<StatAlias>>(&fs::read_to_string(path)?)?
+    } else {
+        vec![]
+    };
+
+    aliases.extend(DEFAULT_ALIASES.iter().map(|&(alias, stat_type, stat_name)| StatAlias {
+        alias: alias.to_string(),
+        stat_type: stat_type.map(|t| t.to_string()),
+        stat_name: stat_name.to_string(),
+    }));
+
+    Ok(aliases)
+}
+
+/// Get the stat type and stat name a stat name given by a player stands for
+pub fn resolve_alias(stat_type: String, stat_name: String) -> (String, String) {
+    let aliases = match ALIASES.get() {
+        Some(aliases) => aliases,
+        // Loading is tried again next time when it fails
+        None => match load_aliases() {
+            Ok(aliases) => ALIASES.get_or_init(|| aliases),
+            Err(e) => {
+                println!("Error loading stat aliases: {:?}", e);
+                return find_alias(&[], stat_type, stat_name);
+            },
+        },
+    };
+
+    find_alias(aliases, stat_type, stat_name)
+}
+
+/// Get the stat type and stat name a stat name stands for in the aliases
+pub fn find_alias(aliases: &[StatAlias], stat_type: String, stat_name: String) -> (String, String) {
+    let normalized = stat_name.trim().replace('_', " ").to_lowercase();
+
+    match aliases.iter().find(|a| a.alias == normalized) {
+        Some(alias) => (alias.stat_type.clone().unwrap_or(stat_type), alias.stat_name.clone()),
+        None => (stat_type, normalized),
+    }
+}
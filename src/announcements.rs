@@ -0,0 +1,233 @@
+use crate::{
+    bot_error::BotResult,
+    leaderboard::SortOrder,
+    links::load_account_links,
+    scheduled_leaderboards::Leaderboard,
+    snapshots::stat_key,
+    stat::Stat,
+    utils::*,
+};
+
+use serenity::{
+    http::{ client::Http, CacheHttp },
+    model::id::ChannelId,
+};
+
+use serde::{ Deserialize, Serialize };
+use std::{ collections::HashMap, env, fs, path::Path };
+
+const ANNOUNCEMENTS_STATE_FILE: &str = "announcements.ron";
+
+/// Leaders and season records of the announced leaderboards, saved between refreshes
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AnnouncementsState {
+    pub season: String,
+    /// By stat key of the leaderboard
+    #[serde(default)]
+    pub boards: HashMap<String, BoardState>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardState {
+    pub leader: Holder,
+    /// Most gained during the season by a player, only on the leaderboards where more is better
+    #[serde(default)]
+    pub record: Option<Holder>,
+    /// Values of the players when the season started for the leaderboard, by trimmed uuid
+    #[serde(default)]
+    pub start: HashMap<String, f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Holder {
+    pub uuid: String,
+    pub username: String,
+    pub value: f64,
+}
+
+pub enum AnnouncementKind {
+    NewLeader,
+    Record,
+}
+
+pub struct Announcement {
+    pub kind: AnnouncementKind,
+    pub stat_type: String,
+    pub stat_name: String,
+    /// The value of a record is the value gained during the season
+    pub leader: Holder,
+    /// Previous leader, or previous record holder for records
+    pub previous: Holder,
+}
+
+/// Load the state of the current season, the records of a previous season are forgotten
+pub fn load_announcements_state(season: &str) -> BotResult<AnnouncementsState> {
+    let state = match Path::new(ANNOUNCEMENTS_STATE_FILE).exists() {
+        true => ron::de::from_str::<AnnouncementsState>(&fs::read_to_string(ANNOUNCEMENTS_STATE_FILE)?)?,
+        false => AnnouncementsState::default(),
+    };
+
+    if state.season != season {
+        return Ok(AnnouncementsState { season: season.to_string(), boards: HashMap::new() });
+    }
+
+    Ok(state)
+}
+
+pub fn save_announcements_state(state: &AnnouncementsState) -> BotResult<()> {
+    fs::write(ANNOUNCEMENTS_STATE_FILE, ron::ser::to_string(state)?)?;
+
+    Ok(())
+}
+
+/// Compare a leaderboard to the previous refresh, from the values of every player, the ranking of
+/// the leaderboard and the shown part of the ranking. Records are the most gained during the season,
+/// counted from the values of the first refresh of the season. Taking first place or the record back
+/// from yourself isn't announced
+pub fn detect_announcements(
+    leaderboard: &Leaderboard,
+    values: &[Stat],
+    ranking: &[Stat],
+    shown: &[Stat],
+    state: &mut AnnouncementsState,
+) -> Vec<Announcement> {
+    let first = match ranking.first() {
+        Some(first) => first,
+        None => return vec![],
+    };
+    let key = stat_key(&leaderboard.stat_type, &leaderboard.stat_name);
+    // Only the shown players have their username, the others are looked up if they're announced
+    let holder = |stat: &Stat, value: f64| Holder {
+        uuid: trim_uuid(&stat.uuid),
+        username: shown.iter().find(|s| s.uuid == stat.uuid).map(|s| s.username.clone()).unwrap_or_default(),
+        value,
+    };
+    let leader = holder(first, first.value);
+
+    let board = match state.boards.get_mut(&key) {
+        Some(board) => board,
+        None => {
+            let start = values.iter().map(|s| (trim_uuid(&s.uuid), s.value)).collect();
+            state.boards.insert(key, BoardState { leader, record: None, start });
+            return vec![];
+        },
+    };
+
+    let announcement = |kind, leader: Holder, previous| Announcement {
+        kind,
+        stat_type: leaderboard.stat_type.clone(),
+        stat_name: leaderboard.stat_name.clone(),
+        leader,
+        previous,
+    };
+    let mut announcements = vec![];
+
+    let previous_leader = std::mem::replace(&mut board.leader, leader.clone());
+    if previous_leader.uuid != leader.uuid {
+        announcements.push(announcement(AnnouncementKind::NewLeader, leader, previous_leader));
+    }
+
+    // Gaining the fewest deaths isn't a record
+    if leaderboard.options.order == SortOrder::Ascending {
+        return announcements;
+    }
+
+    let best = ranking
+        .iter()
+        .map(|s| (s, s.value - board.start.get(&trim_uuid(&s.uuid)).copied().unwrap_or(0.0)))
+        .fold(None, |best: Option<(&Stat, f64)>, (s, gain)| match best {
+            Some((_, best_gain)) if best_gain >= gain => best,
+            _ => Some((s, gain)),
+        })
+        .filter(|&(_, gain)| gain > 0.0)
+        .map(|(s, gain)| holder(s, gain));
+
+    match (best, board.record.clone()) {
+        (Some(best), Some(record)) if best.value > record.value => {
+            board.record = Some(best.clone());
+            if best.uuid != record.uuid {
+                announcements.push(announcement(AnnouncementKind::Record, best, record));
+            }
+        },
+        (Some(best), None) => board.record = Some(best),
+        _ => (),
+    }
+
+    announcements
+}
+
+/// Channel of the announcements, from the ANNOUNCEMENTS_CHANNEL environment variable.
+/// Nothing is announced without it
+pub fn announcements_channel() -> Option<ChannelId> {
+    env::var("ANNOUNCEMENTS_CHANNEL").ok().and_then(|c| c.parse::<u64>().ok()).map(ChannelId)
+}
+
+/// Whether the linked Discord users of the announced players are pinged,
+/// from the ANNOUNCEMENTS_PING environment variable
+pub fn announcements_ping() -> bool {
+    env::var("ANNOUNCEMENTS_PING").is_ok_and(|ping| ping == "true")
+}
+
+pub async fn post_announcements(
+    http: impl AsRef<Http> + CacheHttp,
+    announcements: Vec<Announcement>,
+) -> BotResult<()> {
+    let channel = match announcements_channel() {
+        Some(channel) => channel,
+        None => return Ok(()),
+    };
+    let ping = announcements_ping();
+    let links = load_account_links()?;
+
+    for mut announcement in announcements {
+        for holder in [&mut announcement.leader, &mut announcement.previous] {
+            if holder.username.is_empty() {
+                holder.username = get_username_from_uuid(untrim_uuid(holder.uuid.clone())).await?;
+            }
+        }
+
+        let stat_title = make_stat_title(
+            &mut announcement.stat_type.clone(), &mut announcement.stat_name.clone()
+        );
+        let value = |holder: &Holder| format_stat_value(&announcement.stat_name, holder.value);
+
+        let (title, description) = match announcement.kind {
+            AnnouncementKind::NewLeader => (
+                format!("New leader in {}", stat_title),
+                format!(
+                    "{} took first place from {} with {}",
+                    announcement.leader.username, announcement.previous.username, value(&announcement.leader)
+                ),
+            ),
+            AnnouncementKind::Record => (
+                format!("New season record in {}", stat_title),
+                format!(
+                    "{} broke the record of {} ({} this season) with {}",
+                    announcement.leader.username, announcement.previous.username,
+                    value(&announcement.previous), value(&announcement.leader)
+                ),
+            ),
+        };
+
+        // Mentions in embeds don't ping, so they go in the content
+        let mentions = links.linked
+            .iter()
+            .filter(|(_, uuid)| ping && **uuid == announcement.leader.uuid)
+            .map(|(user_id, _)| format!("<@{}>", user_id))
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        channel.send_message(&http, |message| {
+            message
+                .content(mentions)
+                .embed(|e| e
+                    .title(title)
+                    .description(description)
+                    .thumbnail(format!("https://crafatar.com/avatars/{}", announcement.leader.uuid))
+                    .color((200, 255, 0))
+                )
+        }).await?;
+    }
+
+    Ok(())
+}
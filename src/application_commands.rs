@@ -1,5 +1,8 @@
+use crate::compare::PRESETS;
+
+use serde_json::Value;
 use serenity::{
-    builder::CreateApplicationCommands,
+    builder::{ CreateApplicationCommandOption, CreateApplicationCommands },
     model::{
         interactions::{
             ApplicationCommandOptionType,
@@ -7,76 +10,400 @@ use serenity::{
     },
 };
 
+const STAT_TYPES: [&str; 10] = [
+    "killed", "mined", "broken", "dropped", "picked up", "crafted", "used", "killed by", "custom", "derived",
+];
+
+/// The stat type option with its choices, the description and whether it's required are left to the command
+fn stat_type_option(option: &mut CreateApplicationCommandOption) -> &mut CreateApplicationCommandOption {
+    option.name("stat-type").kind(ApplicationCommandOptionType::String);
+
+    for stat_type in STAT_TYPES.iter() {
+        option.add_string_choice(stat_type, stat_type);
+    }
+
+    option
+}
+
 pub fn create_application_commands(commands: &mut CreateApplicationCommands) -> &mut CreateApplicationCommands {
     commands
         .create_application_command(|command|{
             command
                 .name("stat")
                 .description("Get one stat for a specific player")
+                .create_option(|option| {
+                    stat_type_option(option)
+                        .description("The type of the stat you want")
+                        .required(true)
+                })
+                .create_option(|option| {
+                    option
+                        .name("stat-name")
+                        .description("The name of the stat you want (Any block, item or mob. Can be incompatible with the type you chose)")
+                        .required(true)
+                        .kind(ApplicationCommandOptionType::String)
+                })
                 .create_option(|option| {
                     option
                         .name("player")
-                        .description("Minecraft username of the plyer you want to see the specified stat for")
+                        .description("Minecraft username of the player you want to see the stat for (default: your linked account)")
                         .kind(ApplicationCommandOptionType::String)
+                        .required(false)
+                })
+        })
+        .create_application_command(|command| {
+            command
+                .name("leaderboard")
+                .description("Get the leaderboard for a specific stat")
+                .create_option(|option| {
+                    stat_type_option(option)
+                        .description("The type of the stat you want")
                         .required(true)
                 })
                 .create_option(|option| {
                     option
-                        .name("stat-type")
-                        .description("The type of the stat you want")
+                        .name("stat-name")
+                        .description("The name of the stat you want (Any block, item or mob. Can be incompatible with the type you chose)")
                         .required(true)
                         .kind(ApplicationCommandOptionType::String)
-                        .add_string_choice("killed", "killed")
-                        .add_string_choice("mined", "mined")
-                        .add_string_choice("broken", "broken")
-                        .add_string_choice("dropped", "dropped")
-                        .add_string_choice("picked up", "picked up")
-                        .add_string_choice("crafted", "crafted")
-                        .add_string_choice("used", "used")
-                        .add_string_choice("killed by", "killed by")
-                        .add_string_choice("custom", "custom")
+                })
+                .create_option(|option| {
+                    option
+                        .name("limit")
+                        .description("Number of players shown per page of the leaderboard (default: 10, max: 25)")
+                        .required(false)
+                        .kind(ApplicationCommandOptionType::Integer)
+                })
+                .create_option(|option| {
+                    option
+                        .name("player")
+                        .description("Minecraft username of a player to show with the players ranked around them")
+                        .required(false)
+                        .kind(ApplicationCommandOptionType::String)
+                })
+                .create_option(|option| {
+                    option
+                        .name("order")
+                        .description("Whether the players with the highest or the lowest stat come first (default: highest)")
+                        .required(false)
+                        .kind(ApplicationCommandOptionType::String)
+                        .add_string_choice("highest first", "highest first")
+                        .add_string_choice("lowest first", "lowest first")
+                })
+                .create_option(|option| {
+                    option
+                        .name("min-play-hours")
+                        .description("Only rank the players who played at least that many hours")
+                        .required(false)
+                        .kind(ApplicationCommandOptionType::Integer)
+                })
+                .create_option(|option| {
+                    option
+                        .name("include-ties")
+                        .description("Also show the players tied with the last one shown (default: false)")
+                        .required(false)
+                        .kind(ApplicationCommandOptionType::Boolean)
+                })
+                .create_option(|option| {
+                    option
+                        .name("season")
+                        .description("Show the final standings of a finished season instead, as they were ranked")
+                        .required(false)
+                        .kind(ApplicationCommandOptionType::String)
+                })
+        })
+        .create_application_command(|command| {
+            command
+                .name("rank")
+                .description("Get the ranks of a player in the stats of the leaderboards channel, or in one stat")
+                .create_option(|option| {
+                    stat_type_option(option)
+                        .description("The type of the stat you want, if you only want one")
+                        .required(false)
                 })
                 .create_option(|option| {
                     option
                         .name("stat-name")
-                        .description("The name of the stat you want (Any block, item or mob. Can be incompatible with the type you chose)")
+                        .description("The name of the stat you want, if you only want one")
+                        .required(false)
+                        .kind(ApplicationCommandOptionType::String)
+                })
+                .create_option(|option| {
+                    option
+                        .name("player")
+                        .description("Minecraft username of the player you want to see the ranks of (default: your linked account)")
+                        .kind(ApplicationCommandOptionType::String)
+                        .required(false)
+                })
+        })
+        .create_application_command(|command| {
+            command
+                .name("compare")
+                .description("Compare a stat, or a group of stats, between two or more players")
+                .create_option(|option| {
+                    option
+                        .name("player1")
+                        .description("Minecraft username of the first player to compare")
+                        .kind(ApplicationCommandOptionType::String)
                         .required(true)
+                })
+                .create_option(|option| {
+                    option
+                        .name("player2")
+                        .description("Minecraft username of the second player to compare")
+                        .kind(ApplicationCommandOptionType::String)
+                        .required(true)
+                })
+                .create_option(|option| {
+                    stat_type_option(option)
+                        .description("The type of the stat you want to compare")
+                        .required(false)
+                })
+                .create_option(|option| {
+                    option
+                        .name("stat-name")
+                        .description("The name of the stat you want to compare")
+                        .required(false)
+                        .kind(ApplicationCommandOptionType::String)
+                })
+                .create_option(|option| {
+                    option
+                        .name("preset")
+                        .description("A group of stats to compare instead of a single stat")
+                        .required(false)
+                        .kind(ApplicationCommandOptionType::String);
+                    for (name, _) in PRESETS.iter() {
+                        option.add_string_choice(name, name);
+                    }
+                    option
+                })
+                .create_option(|option| {
+                    option
+                        .name("player3")
+                        .description("Minecraft username of a third player to compare")
+                        .kind(ApplicationCommandOptionType::String)
+                        .required(false)
+                })
+                .create_option(|option| {
+                    option
+                        .name("player4")
+                        .description("Minecraft username of a fourth player to compare")
                         .kind(ApplicationCommandOptionType::String)
+                        .required(false)
                 })
         })
         .create_application_command(|command| {
             command
-                .name("leaderboard")
-                .description("Get the leaderboard for a specific stat")
+                .name("profile")
+                .description("Get an overview of the stats of a player")
+                .create_option(|option| {
+                    option
+                        .name("player")
+                        .description("Minecraft username of the player you want to see the profile of (default: your linked account)")
+                        .kind(ApplicationCommandOptionType::String)
+                        .required(false)
+                })
+        })
+        .create_application_command(|command| {
+            command
+                .name("privacy")
+                .description("Choose whether a player is shown on the leaderboards");
+            for (name, description) in [
+                ("hide", "Stop showing your linked account on the leaderboards"),
+                ("show", "Show one of your accounts on the leaderboards again"),
+                ("exclude", "Exclude a player from the leaderboards, such as a banned or alt account (admins only)"),
+                ("include", "Stop excluding a player from the leaderboards (admins only)"),
+            ].iter() {
+                command.create_option(|option| {
+                    option
+                        .name(name)
+                        .description(description)
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|sub_option| {
+                            sub_option
+                                .name("player")
+                                .description("Minecraft username of the player")
+                                .kind(ApplicationCommandOptionType::String)
+                                .required(true)
+                        })
+                });
+            }
+            command
+        })
+        .create_application_command(|command| {
+            command
+                .name("link")
+                .description("Link your Discord account to your Minecraft account, to leave out the player in commands")
                 .create_option(|option| {
                     option
-                        .name("stat-type")
+                        .name("player")
+                        .description("Your Minecraft username")
+                        .kind(ApplicationCommandOptionType::String)
+                        .required(true)
+                })
+        })
+        .create_application_command(|command| {
+            command
+                .name("unlink")
+                .description("Unlink your Discord account from your Minecraft account")
+        })
+        .create_application_command(|command| {
+            // Shown in the context menu of the members instead of as a slash command,
+            // which the builder doesn't know about yet
+            command.name("Minecraft stats").0.insert("type", Value::from(2));
+            command
+        })
+        .create_application_command(|command| {
+            command
+                .name("subscribe")
+                .description("Get a DM when someone passes you in a stat of the leaderboards channel")
+                .create_option(|option| {
+                    stat_type_option(option)
                         .description("The type of the stat you want")
                         .required(true)
+                })
+                .create_option(|option| {
+                    option
+                        .name("stat-name")
+                        .description("The name of the stat you want")
+                        .required(true)
                         .kind(ApplicationCommandOptionType::String)
-                        .add_string_choice("killed", "killed")
-                        .add_string_choice("mined", "mined")
-                        .add_string_choice("broken", "broken")
-                        .add_string_choice("dropped", "dropped")
-                        .add_string_choice("picked up", "picked up")
-                        .add_string_choice("crafted", "crafted")
-                        .add_string_choice("used", "used")
-                        .add_string_choice("killed by", "killed by")
-                        .add_string_choice("custom", "custom")
+                })
+                .create_option(|option| {
+                    option
+                        .name("top")
+                        .description("Also get a DM when you enter the top N players of the stat")
+                        .required(false)
+                        .kind(ApplicationCommandOptionType::Integer)
+                })
+        })
+        .create_application_command(|command| {
+            command
+                .name("unsubscribe")
+                .description("Stop getting DMs about a stat, or about every stat")
+                .create_option(|option| {
+                    stat_type_option(option)
+                        .description("The type of the stat, leave out to unsubscribe from every stat")
+                        .required(false)
                 })
                 .create_option(|option| {
                     option
                         .name("stat-name")
-                        .description("The name of the stat you want (Any block, item or mob. Can be incompatible with the type you chose)")
+                        .description("The name of the stat, leave out to unsubscribe from every stat")
+                        .required(false)
+                        .kind(ApplicationCommandOptionType::String)
+                })
+        })
+        .create_application_command(|command| {
+            command
+                .name("season")
+                .description("Start, end or list the seasons")
+                .create_option(|option| {
+                    option
+                        .name("start")
+                        .description("Start a new season, counting the stats from now (admins only)")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|sub_option| {
+                            sub_option
+                                .name("name")
+                                .description("Name of the season")
+                                .kind(ApplicationCommandOptionType::String)
+                                .required(true)
+                        })
+                        .create_sub_option(|sub_option| {
+                            sub_option
+                                .name("days")
+                                .description("End the season automatically after that many days")
+                                .kind(ApplicationCommandOptionType::Integer)
+                                .required(false)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("end")
+                        .description("End the current season and post its final standings (admins only)")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                })
+                .create_option(|option| {
+                    option
+                        .name("list")
+                        .description("List the current and past seasons")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                })
+        })
+        .create_application_command(|command| {
+            command
+                .name("halloffame")
+                .description("Get the winners of the past seasons and the season records, of every stat or of one")
+                .create_option(|option| {
+                    stat_type_option(option)
+                        .description("The type of the stat you want, if you only want one")
+                        .required(false)
+                })
+                .create_option(|option| {
+                    option
+                        .name("stat-name")
+                        .description("The name of the stat you want, if you only want one")
+                        .required(false)
+                        .kind(ApplicationCommandOptionType::String)
+                })
+        })
+        .create_application_command(|command| {
+            command
+                .name("history")
+                .description("Get a chart of a stat of a player over time, optionally with other players")
+                .create_option(|option| {
+                    stat_type_option(option)
+                        .description("The type of the stat you want")
+                        .required(true)
+                })
+                .create_option(|option| {
+                    option
+                        .name("stat-name")
+                        .description("The name of the stat you want")
                         .required(true)
                         .kind(ApplicationCommandOptionType::String)
                 })
                 .create_option(|option| {
                     option
-                        .name("limit")
-                        .description("Limit the number of players on the leaderboard (default: 10, max: 25)")
+                        .name("player")
+                        .description("Minecraft username of the player (default: your linked account)")
+                        .required(false)
+                        .kind(ApplicationCommandOptionType::String)
+                })
+                .create_option(|option| {
+                    option
+                        .name("days")
+                        .description("How many days back the chart goes (default: 30)")
                         .required(false)
                         .kind(ApplicationCommandOptionType::Integer)
-                }) 
+                });
+            for name in ["player2", "player3", "player4", "player5"].iter() {
+                command.create_option(|option| {
+                    option
+                        .name(name)
+                        .description("Minecraft username of another player to draw on the chart")
+                        .required(false)
+                        .kind(ApplicationCommandOptionType::String)
+                });
+            }
+            command
+        })
+        .create_application_command(|command| {
+            command
+                .name("server-stats")
+                .description("Get the total, mean and median of a stat over every player of the server")
+                .create_option(|option| {
+                    stat_type_option(option)
+                        .description("The type of the stat you want")
+                        .required(true)
+                })
+                .create_option(|option| {
+                    option
+                        .name("stat-name")
+                        .description("The name of the stat you want")
+                        .required(true)
+                        .kind(ApplicationCommandOptionType::String)
+                })
         })
 }
@@ -1,17 +1,27 @@
 #[derive(Debug)]
 pub enum BotError {
     Error(String),
+    UnknownPlayer(String),
     ReqwestError(reqwest::Error),
     SerenityError(serenity::prelude::SerenityError),
     StdError(std::io::Error),
     RonError(ron::Error),
+    PngError(png::EncodingError),
 }
 
 pub type BotResult<T> = Result<T, BotError>;
 
 impl std::fmt::Display for BotError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+        match self {
+            BotError::Error(e) => write!(f, "{}", e),
+            BotError::UnknownPlayer(player) => write!(f, "Error: The player {} doesn't exist", player),
+            BotError::ReqwestError(e) => write!(f, "{}", e),
+            BotError::SerenityError(e) => write!(f, "{}", e),
+            BotError::StdError(e) => write!(f, "{}", e),
+            BotError::RonError(e) => write!(f, "{}", e),
+            BotError::PngError(e) => write!(f, "{}", e),
+        }
     }
 }
 
@@ -50,3 +60,9 @@ impl From<ron::Error> for BotError {
         BotError::RonError(err)
     }
 }
+
+impl From<png::EncodingError> for BotError {
+    fn from(err: png::EncodingError) -> BotError {
+        BotError::PngError(err)
+    }
+}
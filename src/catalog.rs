@@ -0,0 +1,87 @@
+use crate::derived::{ load_derived_stats, DERIVED_STAT_TYPE };
+
+// Names of the stats known to the bot, as players type them, used to suggest corrections
+
+pub const CUSTOM_STATS: &[&str] = &[
+    "animals bred", "aviate one cm", "bell ring", "boat one cm", "clean armor", "clean banner",
+    "clean shulker box", "climb one cm", "crouch one cm", "damage absorbed", "damage blocked by shield",
+    "damage dealt", "damage dealt absorbed", "damage dealt resisted", "damage resisted", "damage taken",
+    "deaths", "drop", "eat cake slice", "enchant item", "fall one cm", "fill cauldron", "fish caught",
+    "fly one cm", "horse one cm", "inspect dispenser", "inspect dropper", "inspect hopper",
+    "interact with anvil", "interact with beacon", "interact with blast furnace",
+    "interact with brewingstand", "interact with campfire", "interact with cartography table",
+    "interact with crafting table", "interact with furnace", "interact with grindstone",
+    "interact with lectern", "interact with loom", "interact with smithing table",
+    "interact with smoker", "interact with stonecutter", "jump", "leave game", "minecart one cm",
+    "mob kills", "open barrel", "open chest", "open enderchest", "open shulker box", "pig one cm",
+    "play noteblock", "play record", "play time", "player kills", "pot flower", "raid trigger",
+    "raid win", "sleep in bed", "sneak time", "sprint one cm", "strider one cm", "swim one cm",
+    "talked to villager", "target hit", "time since death", "time since rest", "total world time",
+    "traded with villager", "trigger trapped chest", "tune noteblock", "use cauldron",
+    "walk on water one cm", "walk one cm", "walk under water one cm",
+];
+
+pub const ENTITIES: &[&str] = &[
+    "axolotl", "bat", "bee", "blaze", "cat", "cave spider", "chicken", "cod", "cow", "creeper",
+    "dolphin", "donkey", "drowned", "elder guardian", "ender dragon", "enderman", "endermite",
+    "evoker", "fox", "ghast", "glow squid", "goat", "guardian", "hoglin", "horse", "husk",
+    "iron golem", "llama", "magma cube", "mooshroom", "mule", "ocelot", "panda", "parrot",
+    "phantom", "pig", "piglin", "piglin brute", "pillager", "player", "polar bear", "pufferfish",
+    "rabbit", "ravager", "salmon", "sheep", "shulker", "silverfish", "skeleton", "skeleton horse",
+    "slime", "snow golem", "spider", "squid", "stray", "strider", "trader llama", "tropical fish",
+    "turtle", "vex", "villager", "vindicator", "wandering trader", "witch", "wither",
+    "wither skeleton", "wolf", "zoglin", "zombie", "zombie horse", "zombie villager",
+    "zombified piglin",
+];
+
+// Not every block and item of the game, but the ones players usually look for
+pub const ITEMS: &[&str] = &[
+    "stone", "cobblestone", "deepslate", "cobbled deepslate", "granite", "diorite", "andesite",
+    "tuff", "calcite", "dripstone block", "pointed dripstone", "dirt", "grass block", "coarse dirt",
+    "podzol", "mycelium", "sand", "red sand", "gravel", "clay", "sandstone", "red sandstone",
+    "netherrack", "soul sand", "soul soil", "basalt", "blackstone", "end stone", "obsidian",
+    "crying obsidian", "bedrock", "ice", "packed ice", "blue ice", "snow", "snow block",
+    "coal ore", "deepslate coal ore", "iron ore", "deepslate iron ore", "copper ore",
+    "deepslate copper ore", "gold ore", "deepslate gold ore", "nether gold ore", "redstone ore",
+    "deepslate redstone ore", "lapis ore", "deepslate lapis ore", "diamond ore",
+    "deepslate diamond ore", "emerald ore", "deepslate emerald ore", "nether quartz ore",
+    "ancient debris", "amethyst block", "amethyst cluster", "oak log", "spruce log", "birch log",
+    "jungle log", "acacia log", "dark oak log", "crimson stem", "warped stem", "oak planks",
+    "spruce planks", "birch planks", "jungle planks", "acacia planks", "dark oak planks",
+    "crimson planks", "warped planks", "oak leaves", "spruce leaves", "birch leaves",
+    "jungle leaves", "acacia leaves", "dark oak leaves", "azalea leaves", "glass", "glass pane",
+    "glowstone", "sea lantern", "torch", "lantern", "crafting table", "furnace", "chest",
+    "barrel", "hopper", "rail", "powered rail", "tnt", "bookshelf", "stone bricks", "bricks",
+    "wheat", "carrots", "potatoes", "beetroots", "sugar cane", "bamboo", "cactus", "melon",
+    "pumpkin", "kelp", "cocoa", "sweet berry bush", "nether wart", "tall grass", "grass", "fern",
+    "coal", "charcoal", "raw iron", "raw copper", "raw gold", "iron ingot", "copper ingot",
+    "gold ingot", "netherite ingot", "netherite scrap", "diamond", "emerald", "lapis lazuli",
+    "redstone", "quartz", "amethyst shard", "stick", "string", "bone", "bone meal", "gunpowder",
+    "rotten flesh", "spider eye", "ender pearl", "ender eye", "blaze rod", "ghast tear",
+    "slime ball", "leather", "feather", "egg", "arrow", "bow", "crossbow", "trident", "shield",
+    "totem of undying", "elytra", "firework rocket", "fishing rod", "flint and steel", "shears",
+    "bucket", "water bucket", "lava bucket", "wooden pickaxe", "stone pickaxe", "iron pickaxe",
+    "golden pickaxe", "diamond pickaxe", "netherite pickaxe", "wooden sword", "stone sword",
+    "iron sword", "golden sword", "diamond sword", "netherite sword", "wooden axe", "stone axe",
+    "iron axe", "golden axe", "diamond axe", "netherite axe", "wooden shovel", "stone shovel",
+    "iron shovel", "golden shovel", "diamond shovel", "netherite shovel", "wooden hoe",
+    "stone hoe", "iron hoe", "golden hoe", "diamond hoe", "netherite hoe", "bread", "apple",
+    "golden apple", "enchanted golden apple", "golden carrot", "cooked beef", "cooked porkchop",
+    "cooked chicken", "cooked mutton", "cooked cod", "cooked salmon", "baked potato", "cake",
+    "cookie", "pumpkin pie", "mushroom stew", "beef", "porkchop", "chicken", "mutton",
+    "experience bottle", "enchanted book", "book", "paper", "map", "name tag", "saddle",
+];
+
+/// The stat names known for a stat type
+pub fn stat_names(stat_type: &str) -> Vec<String> {
+    let names: &[&str] = match stat_type {
+        "custom" => CUSTOM_STATS,
+        "killed" | "killed by" => ENTITIES,
+        DERIVED_STAT_TYPE => return load_derived_stats()
+            .map(|stats| stats.into_iter().map(|d| d.name).collect())
+            .unwrap_or_default(),
+        _ => ITEMS,
+    };
+
+    names.iter().map(|name| name.to_string()).collect()
+}
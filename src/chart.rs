@@ -0,0 +1,143 @@
+use crate::bot_error::BotResult;
+
+pub const WIDTH: u32 = 800;
+pub const HEIGHT: u32 = 400;
+// Space around the plot area, in pixels
+const MARGIN: u32 = 20;
+const GRID_LINES: u32 = 5;
+const BACKGROUND: (u8, u8, u8) = (47, 49, 54);
+const GRID: (u8, u8, u8) = (79, 84, 92);
+
+/// Colors of the lines, in the order of the players, with the matching square emojis for the legends
+pub const COLORS: [((u8, u8, u8), &str); 5] = [
+    ((120, 177, 89), "🟩"),
+    ((85, 172, 238), "🟦"),
+    ((221, 46, 68), "🟥"),
+    ((253, 203, 88), "🟨"),
+    ((170, 142, 214), "🟪"),
+];
+
+/// A line of the chart, points are (x, y) in any unit
+pub struct Series {
+    pub color: (u8, u8, u8),
+    pub points: Vec<(f64, f64)>,
+}
+
+/// Smallest and biggest x and y of every series. A range of a single value is widened,
+/// so that flat lines are drawn in the middle of the chart
+pub fn chart_bounds(series: &[Series]) -> ((f64, f64), (f64, f64)) {
+    let points = series.iter().flat_map(|s| s.points.iter());
+    let (mut x_min, mut x_max, mut y_min, mut y_max) = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+
+    for &(x, y) in points {
+        x_min = x_min.min(x);
+        x_max = x_max.max(x);
+        y_min = y_min.min(y);
+        y_max = y_max.max(y);
+    }
+
+    if x_min > x_max {
+        return ((0.0, 1.0), (0.0, 1.0));
+    }
+    if x_min == x_max {
+        x_max = x_min + 1.0;
+    }
+    if y_min == y_max {
+        y_min -= 1.0;
+        y_max += 1.0;
+    }
+
+    ((x_min, x_max), (y_min, y_max))
+}
+
+struct Canvas {
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new() -> Canvas {
+        let mut pixels = Vec::with_capacity((WIDTH * HEIGHT * 3) as usize);
+        for _ in 0..WIDTH * HEIGHT {
+            pixels.extend_from_slice(&[BACKGROUND.0, BACKGROUND.1, BACKGROUND.2]);
+        }
+
+        Canvas { pixels }
+    }
+
+    fn set(&mut self, x: i64, y: i64, color: (u8, u8, u8)) {
+        if x < 0 || y < 0 || x >= WIDTH as i64 || y >= HEIGHT as i64 {
+            return;
+        }
+
+        let index = ((y as u32 * WIDTH + x as u32) * 3) as usize;
+        self.pixels[index..index + 3].copy_from_slice(&[color.0, color.1, color.2]);
+    }
+
+    /// Bresenham's line, drawn three pixels wide when thick
+    fn line(&mut self, (x0, y0): (i64, i64), (x1, y1): (i64, i64), color: (u8, u8, u8), thick: bool) {
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (sx, sy) = (if x0 < x1 { 1 } else { -1 }, if y0 < y1 { 1 } else { -1 });
+        let (mut x, mut y, mut error) = (x0, y0, dx + dy);
+
+        loop {
+            match thick {
+                true => for (ox, oy) in [(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)].iter() {
+                    self.set(x + ox, y + oy, color);
+                },
+                false => self.set(x, y, color),
+            }
+
+            if x == x1 && y == y1 {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += sx;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += sy;
+            }
+        }
+    }
+}
+
+/// Draw the series as lines over horizontal grid lines, the bottom and top ones being
+/// the bounds of `chart_bounds`, and encode the chart as a PNG
+pub fn render_line_chart(series: &[Series]) -> BotResult<Vec<u8>> {
+    let ((x_min, x_max), (y_min, y_max)) = chart_bounds(series);
+    let (plot_width, plot_height) = ((WIDTH - 2 * MARGIN) as f64, (HEIGHT - 2 * MARGIN) as f64);
+    let to_pixel = |(x, y): (f64, f64)| (
+        (MARGIN as f64 + (x - x_min) / (x_max - x_min) * plot_width).round() as i64,
+        (MARGIN as f64 + (y_max - y) / (y_max - y_min) * plot_height).round() as i64,
+    );
+
+    let mut canvas = Canvas::new();
+
+    for i in 0..GRID_LINES {
+        let y = (MARGIN as f64 + plot_height * i as f64 / (GRID_LINES - 1) as f64).round() as i64;
+        canvas.line((MARGIN as i64, y), ((WIDTH - MARGIN) as i64, y), GRID, false);
+    }
+
+    for s in series.iter() {
+        let pixels = s.points.iter().map(|&point| to_pixel(point)).collect::<Vec<(i64, i64)>>();
+
+        match pixels.as_slice() {
+            [single] => canvas.line(*single, *single, s.color, true),
+            _ => for segment in pixels.windows(2) {
+                canvas.line(segment[0], segment[1], s.color, true);
+            },
+        }
+    }
+
+    let mut png = vec![];
+    {
+        let mut encoder = png::Encoder::new(&mut png, WIDTH, HEIGHT);
+        encoder.set_color(png::ColorType::RGB);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&canvas.pixels)?;
+    }
+
+    Ok(png)
+}
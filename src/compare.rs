@@ -0,0 +1,131 @@
+use crate::{
+    aliases::resolve_alias,
+    bot_error::{ BotResult, BotError },
+    stat::{ get_stats, Stat },
+    utils::*,
+};
+
+use serenity::{
+    builder::CreateEmbed,
+    model::interactions::ApplicationCommandInteractionDataOption,
+};
+
+use futures::future::try_join_all;
+
+pub struct CompareCommandArgs {
+    pub players: Vec<String>,
+    pub stats: Vec<(String, String)>,
+}
+
+// Groups of stats that can be compared at once instead of a single stat
+pub const PRESETS: &[(&str, &[(&str, &str)])] = &[
+    ("general", &[
+        ("custom", "play time"),
+        ("custom", "deaths"),
+        ("custom", "jump"),
+    ]),
+    ("combat", &[
+        ("custom", "mob kills"),
+        ("custom", "player kills"),
+        ("custom", "damage dealt"),
+        ("custom", "damage taken"),
+        ("custom", "deaths"),
+    ]),
+    ("mining", &[
+        ("mined", "diamond ore"),
+        ("mined", "deepslate diamond ore"),
+        ("mined", "ancient debris"),
+        ("mined", "stone"),
+        ("mined", "deepslate"),
+    ]),
+    ("movement", &[
+        ("custom", "walk one cm"),
+        ("custom", "sprint one cm"),
+        ("custom", "swim one cm"),
+        ("custom", "boat one cm"),
+        ("custom", "climb one cm"),
+        ("custom", "fly one cm"),
+    ]),
+];
+
+/// Get the stats of every player, grouped by stat. The uuid of each player is only looked up once
+pub async fn get_comparison(args: &CompareCommandArgs) -> BotResult<Vec<Vec<Stat>>> {
+    let stats = args.stats
+        .iter()
+        .map(|(stat_type, stat_name)| (stat_type.as_str(), stat_name.as_str()))
+        .collect::<Vec<(&str, &str)>>();
+    let by_player = try_join_all(args.players.iter().map(|player| get_stats(player, &stats))).await?;
+
+    Ok((0..stats.len())
+        .map(|i| by_player.iter().map(|(_, stats)| stats[i].clone()).collect())
+        .collect())
+}
+
+/// How far behind the best of the players each of them is, 0 for the best
+pub fn gaps_to_leader(stats: &[Stat]) -> Vec<f64> {
+    let leader_value = stats.iter().map(|s| s.value).fold(f64::MIN, f64::max);
+
+    stats.iter().map(|s| leader_value - s.value).collect()
+}
+
+pub fn parse_compare_args(
+    args: &[ApplicationCommandInteractionDataOption]
+) -> BotResult<CompareCommandArgs> {
+    let find_arg = |name: &str| args
+        .iter()
+        .find(|&x| x.name.as_str() == name)
+        .and_then(|data| data.value.as_ref())
+        .and_then(|value| value.as_str())
+        .map(|value| value.to_string());
+
+    let players = ["player1", "player2", "player3", "player4"]
+        .iter()
+        .filter_map(|&name| find_arg(name))
+        .collect();
+
+    let stats = match (find_arg("stat-type"), find_arg("stat-name"), find_arg("preset")) {
+        (Some(stat_type), Some(stat_name), _) => vec![resolve_alias(stat_type, stat_name)],
+        (_, _, Some(preset)) => PRESETS
+            .iter()
+            .find(|(name, _)| *name == preset)
+            .map(|(_, stats)| stats
+                .iter()
+                .map(|&(stat_type, stat_name)| (stat_type.to_string(), stat_name.to_string()))
+                .collect()
+            )
+            .ok_or_else(|| BotError::Error(format!("Error: There is no preset named {}", preset)))?,
+        _ => return Err(BotError::Error(
+            "Error: Give either a stat type and a stat name, or a preset".to_string()
+        )),
+    };
+
+    Ok(CompareCommandArgs { players, stats })
+}
+
+pub fn create_compare_embed<'a>(
+    args: &CompareCommandArgs,
+    comparison: Vec<Vec<Stat>>,
+    embed: &'a mut CreateEmbed
+) -> &'a mut CreateEmbed {
+    embed.title(args.players.join(" vs "));
+    embed.color((200, 255, 0));
+
+    for ((stat_type, stat_name), stats) in args.stats.iter().zip(comparison.iter()) {
+        let stat_title = make_stat_title(&mut stat_type.clone(), &mut stat_name.clone());
+        let gaps = gaps_to_leader(stats);
+
+        let lines = args.players.iter().zip(stats.iter()).zip(gaps.iter()).map(|((player, stat), &gap)| {
+            let value = format_stat_value(stat_name, stat.value);
+
+            if gap == 0.0 {
+                format!("👑 **{}**: **{}**", player, value)
+            } else {
+                format!("{}: {} (-{})", player, value, format_stat_value(stat_name, gap))
+            }
+        }).collect::<Vec<String>>();
+
+        embed.field(stat_title, lines.join("\n"), false);
+    }
+
+    embed
+}
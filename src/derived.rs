@@ -0,0 +1,212 @@
+use crate::{
+    bot_error::{ BotResult, BotError },
+    expression::Expression,
+    leaderboard::fetch_unsorted_ranking,
+    stat::Stat,
+    utils::*,
+};
+
+use serde::Deserialize;
+use futures::future::{ try_join_all, BoxFuture, FutureExt };
+use std::{ collections::HashMap, fs };
+
+/// Type of the stats defined in derived_stats.ron, stat names are the names of the derived stats
+pub const DERIVED_STAT_TYPE: &str = "derived";
+
+const DERIVED_STATS_FILE: &str = "derived_stats.ron";
+
+// Derived stats can be made of other derived stats, this stops definitions referencing themselves
+const MAX_DEPTH: usize = 8;
+
+const TICKS_PER_HOUR: f64 = 20.0 * 60.0 * 60.0;
+
+/// A stat type and a stat name, as they're given to the commands
+pub type StatRef = (String, String);
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum DerivedKind {
+    Sum(Vec<StatRef>),
+    Ratio(StatRef, StatRef),
+    PerHour(StatRef),
+    Expression(Expression),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DerivedStat {
+    pub name: String,
+    pub kind: DerivedKind,
+}
+
+impl DerivedStat {
+    /// The stats this one is computed from
+    fn components(&self) -> Vec<StatRef> {
+        match self.kind {
+            DerivedKind::Sum(ref stats) => stats.clone(),
+            DerivedKind::Ratio(ref dividend, ref divisor) => vec![dividend.clone(), divisor.clone()],
+            DerivedKind::PerHour(ref stat) => vec![
+                stat.clone(),
+                ("custom".to_string(), "play time".to_string()),
+            ],
+            DerivedKind::Expression(ref expression) => expression.stats(),
+        }
+    }
+
+    /// Compute the value from the values of the components, in the same order.
+    /// Returns None when the value doesn't make sense, like a rate without any play time
+    fn combine(&self, values: &[f64]) -> Option<f64> {
+        match self.kind {
+            DerivedKind::Sum(_) => Some(values.iter().sum()),
+            // Like for kill/death ratios, dividing by 0 counts as dividing by 1
+            DerivedKind::Ratio(_, _) => Some(values[0] / values[1].max(1.0)),
+            DerivedKind::PerHour(_) => {
+                let hours = values[1] / TICKS_PER_HOUR;
+                if hours > 0.0 { Some(values[0] / hours) } else { None }
+            },
+            DerivedKind::Expression(ref expression) => {
+                let values = expression.stats().into_iter().zip(values.iter().copied()).collect();
+                expression.evaluate(&values)
+            },
+        }
+    }
+}
+
+pub fn load_derived_stats() -> BotResult<Vec<DerivedStat>> {
+    load_derived_stats_from(DERIVED_STATS_FILE)
+}
+
+pub fn load_derived_stats_from(path: &str) -> BotResult<Vec<DerivedStat>> {
+    let derived_stats = fs::read_to_string(path)?;
+
+    Ok(ron::de::from_str(&derived_stats)?)
+}
+
+fn find_derived_stat(name: &str) -> BotResult<DerivedStat> {
+    load_derived_stats()?
+        .into_iter()
+        .find(|d| d.name == name)
+        .ok_or_else(|| BotError::Error(format!("Error: There is no derived stat named {}", name)))
+}
+
+/// Get the value of a derived stat for every player
+pub async fn fetch_derived_ranking(name: &str) -> BotResult<Vec<Stat>> {
+    let values = fetch_all_values(DERIVED_STAT_TYPE.to_string(), name.to_string(), 0).await?;
+
+    Ok(values
+        .into_iter()
+        .map(|(uuid, value)| Stat { success: true, uuid, username: String::new(), value })
+        .collect())
+}
+
+/// Get the value of an expression for every player, the name is only used in errors
+pub async fn fetch_expression_ranking(name: &str, expression: &Expression) -> BotResult<Vec<Stat>> {
+    let derived = DerivedStat {
+        name: name.to_string(),
+        kind: DerivedKind::Expression(expression.clone()),
+    };
+    let values = fetch_derived_values(derived, 0).await?;
+
+    Ok(values
+        .into_iter()
+        .map(|(uuid, value)| Stat { success: true, uuid, username: String::new(), value })
+        .collect())
+}
+
+/// Get the value of a derived stat for a player
+pub async fn fetch_derived_stat(uuid: &str, name: &str) -> BotResult<Stat> {
+    let value = fetch_player_value(uuid.to_string(), DERIVED_STAT_TYPE.to_string(), name.to_string(), 0).await?;
+
+    Ok(Stat {
+        success: value.is_some(),
+        uuid: uuid.to_string(),
+        username: String::new(),
+        value: value.unwrap_or(0.0),
+    })
+}
+
+fn fetch_all_values(
+    stat_type: String,
+    stat_name: String,
+    depth: usize,
+) -> BoxFuture<'static, BotResult<HashMap<String, f64>>> {
+    async move {
+        if stat_type != DERIVED_STAT_TYPE {
+            let stats = fetch_unsorted_ranking(stat_type, stat_name).await?;
+
+            return Ok(stats.into_iter().map(|s| (s.uuid, s.value)).collect());
+        }
+
+        fetch_derived_values(find_derived_stat(&stat_name)?, depth).await
+    }.boxed()
+}
+
+fn fetch_derived_values(
+    derived: DerivedStat,
+    depth: usize,
+) -> BoxFuture<'static, BotResult<HashMap<String, f64>>> {
+    async move {
+        if depth >= MAX_DEPTH {
+            return Err(BotError::Error(format!("Error: The derived stat {} references itself", derived.name)));
+        }
+
+        let components = try_join_all(derived
+            .components()
+            .into_iter()
+            .map(|(stat_type, stat_name)| fetch_all_values(stat_type, stat_name, depth + 1))
+        ).await?;
+
+        let mut uuids = components.iter().flat_map(|c| c.keys().cloned()).collect::<Vec<String>>();
+        uuids.sort();
+        uuids.dedup();
+
+        // Players missing a component just have 0 in it
+        Ok(uuids
+            .into_iter()
+            .filter_map(|uuid| {
+                let values = components
+                    .iter()
+                    .map(|c| c.get(&uuid).copied().unwrap_or(0.0))
+                    .collect::<Vec<f64>>();
+                derived.combine(&values).map(|value| (uuid, value))
+            })
+            .collect())
+    }.boxed()
+}
+
+fn fetch_player_value(
+    uuid: String,
+    stat_type: String,
+    stat_name: String,
+    depth: usize,
+) -> BoxFuture<'static, BotResult<Option<f64>>> {
+    async move {
+        if stat_type != DERIVED_STAT_TYPE {
+            let request = format!(
+                "{}/api/v1/stats?uuid={}&stat_type={}&stat_name={}",
+                SERVER_ADDRESS, uuid, name_to_minecraft_id(stat_type), name_to_minecraft_id(stat_name)
+            );
+
+            let stat = reqwest::get(request)
+                .await?
+                .json::<Vec<Stat>>()
+                .await?
+                .pop();
+
+            return Ok(Some(stat.filter(|s| s.success).map(|s| s.value).unwrap_or(0.0)));
+        }
+
+        if depth >= MAX_DEPTH {
+            return Err(BotError::Error(format!("Error: The derived stat {} references itself", stat_name)));
+        }
+
+        let derived = find_derived_stat(&stat_name)?;
+        let components = try_join_all(derived
+            .components()
+            .into_iter()
+            .map(|(stat_type, stat_name)| fetch_player_value(uuid.clone(), stat_type, stat_name, depth + 1))
+        ).await?;
+
+        let values = components.into_iter().map(|v| v.unwrap_or(0.0)).collect::<Vec<f64>>();
+
+        Ok(derived.combine(&values))
+    }.boxed()
+}
@@ -0,0 +1,291 @@
+use crate::{
+    announcements::announcements_channel,
+    bot_error::BotResult,
+    leaderboard::{ competition_ranks, fetch_unsorted_ranking, filter_ranking, RankingOptions },
+    privacy::load_privacy_list,
+    scheduled_leaderboards::{ Leaderboard, LeaderboardMessage },
+    snapshots::{ first_snapshot_since, latest_snapshot, stat_key, Snapshot },
+    stat::Stat,
+    utils::*,
+};
+
+use serenity::{
+    builder::CreateEmbed,
+    http::{ client::Http, CacheHttp },
+    model::id::ChannelId,
+    utils::hashmap_to_json_map,
+};
+
+use serde_json::Value;
+use std::{ collections::{ hash_map::Entry, HashMap, HashSet }, env, fs, path::Path };
+
+const DIGEST_STATE_FILE: &str = "last_digest.ron";
+const WEEK: u64 = 60 * 60 * 24 * 7;
+// Players shown per stat in the digest
+const DIGEST_TOP: usize = 3;
+// Discord doesn't allow more fields than that in an embed
+const MAX_FIELDS: usize = 25;
+// Keeps the new players field under the length limit of fields
+const MAX_NEW_PLAYERS_SHOWN: usize = 20;
+
+/// Change of a player in a stat between two snapshots
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerChange {
+    pub uuid: String,
+    pub gain: f64,
+    /// Rank before and after, None when the player wasn't ranked
+    pub ranks: (Option<usize>, Option<usize>),
+}
+
+/// Changes of every player of a stat between two snapshots, the biggest gain first.
+/// Empty when the stat wasn't tracked yet in the old snapshot
+pub fn stat_changes(old: &Snapshot, new: &Snapshot, key: &str, options: &RankingOptions) -> BotResult<Vec<PlayerChange>> {
+    if !old.values.contains_key(key) {
+        return Ok(vec![]);
+    }
+
+    // Ranked like the leaderboard was at the time of the snapshot
+    let ranks = |snapshot: &Snapshot| -> BotResult<HashMap<String, usize>> {
+        let stats = |key: &str| snapshot.values
+            .get(key)
+            .map(|values| values.iter().map(|(uuid, &value)| Stat {
+                success: true,
+                uuid: uuid.clone(),
+                username: String::new(),
+                value,
+            }).collect::<Vec<Stat>>())
+            .unwrap_or_default();
+        let ranking = filter_ranking(stats(key), options, &stats(&stat_key("custom", "play time")))?;
+
+        Ok(competition_ranks(&ranking)
+            .into_iter()
+            .zip(ranking)
+            .map(|(rank, s)| (s.uuid, rank))
+            .collect())
+    };
+    let (old_ranks, new_ranks) = (ranks(old)?, ranks(new)?);
+
+    let mut changes = new.values
+        .get(key)
+        .map(|values| values.keys().map(|uuid| PlayerChange {
+            uuid: uuid.clone(),
+            gain: new.value(key, uuid).unwrap_or(0.0) - old.value(key, uuid).unwrap_or(0.0),
+            ranks: (old_ranks.get(uuid).copied(), new_ranks.get(uuid).copied()),
+        }).collect::<Vec<PlayerChange>>())
+        .unwrap_or_default();
+
+    changes.sort_by(|a, b| b.gain
+        .partial_cmp(&a.gain)
+        .unwrap_or(std::cmp::Ordering::Equal)
+        .then_with(|| a.uuid.cmp(&b.uuid))
+    );
+
+    Ok(changes)
+}
+
+/// Players in the new snapshot who weren't in any stat of the old one. Stats not tracked yet
+/// in the old snapshot are skipped
+pub fn new_players(old: &Snapshot, new: &Snapshot) -> Vec<String> {
+    let known = old.values.values().flat_map(|values| values.keys()).collect::<HashSet<&String>>();
+
+    let mut players = new.values
+        .iter()
+        .filter(|(key, _)| old.values.contains_key(*key))
+        .flat_map(|(_, values)| values.keys())
+        .filter(|uuid| !known.contains(uuid))
+        .cloned()
+        .collect::<Vec<String>>();
+    players.sort();
+    players.dedup();
+
+    players
+}
+
+/// Add the play time of every player to the values of the leaderboards when no leaderboard has it,
+/// so that the server play time of the digest can be counted
+pub async fn fetch_digest_values(values: &mut HashMap<String, Vec<Stat>>) -> BotResult<()> {
+    if let Entry::Vacant(entry) = values.entry(stat_key("custom", "play time")) {
+        entry.insert(fetch_unsorted_ranking("custom", "play time").await?);
+    }
+
+    Ok(())
+}
+
+fn load_last_digest() -> BotResult<Option<u64>> {
+    if !Path::new(DIGEST_STATE_FILE).exists() {
+        return Ok(None);
+    }
+
+    Ok(Some(ron::de::from_str(&fs::read_to_string(DIGEST_STATE_FILE)?)?))
+}
+
+/// Channel of the digest, from the DIGEST_CHANNEL environment variable or the announcements channel
+fn digest_channel() -> Option<ChannelId> {
+    env::var("DIGEST_CHANNEL")
+        .ok()
+        .and_then(|c| c.parse::<u64>().ok())
+        .map(ChannelId)
+        .or_else(announcements_channel)
+}
+
+/// Post the digest of the past week if the previous one is a week old.
+/// The first digest is a week after the bot first checks
+pub async fn update_digest(http: impl AsRef<Http> + CacheHttp, messages: &[LeaderboardMessage]) -> BotResult<()> {
+    let last_digest = match load_last_digest()? {
+        Some(last_digest) => last_digest,
+        None => {
+            fs::write(DIGEST_STATE_FILE, ron::ser::to_string(&now())?)?;
+            return Ok(());
+        },
+    };
+
+    if now() < last_digest + WEEK {
+        return Ok(());
+    }
+
+    let channel = match digest_channel() {
+        Some(channel) => channel,
+        None => return Ok(()),
+    };
+    let (old, new) = match (first_snapshot_since(now() - WEEK)?, latest_snapshot()?) {
+        (Some(old), Some(new)) if new.taken_at > old.taken_at => (old, new),
+        _ => return Ok(()),
+    };
+
+    let leaderboards = messages
+        .iter()
+        .flat_map(|m| m.leaderboards.iter().cloned())
+        .collect::<Vec<Leaderboard>>();
+    let embeds = create_digest_embeds(&old, &new, &leaderboards).await?;
+
+    channel.send_message(&http, |message| {
+        message.0.insert("embeds", Value::Array(
+            embeds.into_iter().map(|embed| Value::Object(hashmap_to_json_map(embed.0))).collect()
+        ));
+        message
+    }).await?;
+
+    fs::write(DIGEST_STATE_FILE, ron::ser::to_string(&now())?)?;
+
+    Ok(())
+}
+
+async fn create_digest_embeds(
+    old: &Snapshot,
+    new: &Snapshot,
+    leaderboards: &[Leaderboard],
+) -> BotResult<Vec<CreateEmbed>> {
+    // The server play time counts everyone, the players hidden from the leaderboards aren't shown otherwise
+    let play_time = stat_changes(old, new, &stat_key("custom", "play time"), &RankingOptions::default())?;
+    let privacy_list = load_privacy_list()?;
+    let (old, new) = (&old.without_hidden(&privacy_list), &new.without_hidden(&privacy_list));
+
+    let deaths = stat_changes(old, new, &stat_key("custom", "deaths"), &RankingOptions::default())?;
+    let newcomers = new_players(old, new);
+
+    let boards = leaderboards
+        .iter()
+        .take(MAX_FIELDS)
+        .map(|l| Ok((l, stat_changes(old, new, &stat_key(&l.stat_type, &l.stat_name), &l.options)?)))
+        .collect::<BotResult<Vec<(&Leaderboard, Vec<PlayerChange>)>>>()?;
+
+    // Every username shown is looked up at once
+    let mut uuids = newcomers.iter().take(MAX_NEW_PLAYERS_SHOWN).cloned().collect::<Vec<String>>();
+    uuids.extend(deaths.iter().take(1).map(|c| c.uuid.clone()));
+    for (_, changes) in boards.iter() {
+        uuids.extend(changes.iter().take(DIGEST_TOP).map(|c| c.uuid.clone()));
+        uuids.extend(biggest_climbs(changes).iter().take(DIGEST_TOP).map(|c| c.uuid.clone()));
+    }
+    uuids.sort();
+    uuids.dedup();
+    let names = uuids
+        .iter()
+        .cloned()
+        .zip(get_usernames_from_uuids(uuids.iter().map(|uuid| untrim_uuid(uuid.clone())).collect()).await?)
+        .collect::<HashMap<String, String>>();
+    let name = |uuid: &str| names.get(uuid).cloned().unwrap_or_default();
+
+    let mut overview = CreateEmbed::default();
+    overview
+        .title("Weekly digest")
+        .description(format!("From <t:{}:D> to <t:{}:D>", old.taken_at, new.taken_at))
+        .color((200, 255, 0))
+        .field(
+            "Server play time",
+            match play_time.is_empty() {
+                true => "Not tracked yet".to_string(),
+                false => format_stat_value("play time", play_time.iter().map(|c| c.gain).sum()),
+            },
+            true
+        )
+        .field(
+            "Most deaths",
+            deaths
+                .first()
+                .filter(|c| c.gain > 0.0)
+                .map(|c| format!("{} ({})", name(&c.uuid), format_stat_value("deaths", c.gain)))
+                .unwrap_or_else(|| "Nobody died".to_string()),
+            true
+        )
+        .field(
+            format!("New players ({})", newcomers.len()),
+            match newcomers.len() {
+                0 => "Nobody joined".to_string(),
+                count if count > MAX_NEW_PLAYERS_SHOWN => format!(
+                    "{} and {} more",
+                    newcomers.iter().take(MAX_NEW_PLAYERS_SHOWN).map(|uuid| name(uuid)).collect::<Vec<String>>().join(", "),
+                    count - MAX_NEW_PLAYERS_SHOWN
+                ),
+                _ => newcomers.iter().map(|uuid| name(uuid)).collect::<Vec<String>>().join(", "),
+            },
+            false
+        );
+
+    let mut gainers = CreateEmbed::default();
+    gainers.title("Biggest gainers").color((200, 255, 0));
+    let mut climbers = CreateEmbed::default();
+    climbers.title("Rank changes").color((200, 255, 0));
+
+    for (leaderboard, changes) in boards.iter() {
+        let stat_title = make_stat_title(&mut leaderboard.stat_type.clone(), &mut leaderboard.stat_name.clone());
+
+        let top_gains = changes
+            .iter()
+            .filter(|c| c.gain > 0.0)
+            .take(DIGEST_TOP)
+            .map(|c| format!("{} +{}", name(&c.uuid), format_stat_value(&leaderboard.stat_name, c.gain)))
+            .collect::<Vec<String>>();
+        if !top_gains.is_empty() {
+            gainers.field(&stat_title, top_gains.join("\n"), true);
+        }
+
+        let climbs = biggest_climbs(changes)
+            .iter()
+            .take(DIGEST_TOP)
+            .map(|c| match c.ranks {
+                (Some(before), Some(after)) => format!("{} #{} → #{}", name(&c.uuid), before, after),
+                (_, after) => format!("{} new at #{}", name(&c.uuid), after.unwrap_or_default()),
+            })
+            .collect::<Vec<String>>();
+        if !climbs.is_empty() {
+            climbers.field(&stat_title, climbs.join("\n"), true);
+        }
+    }
+
+    Ok(vec![overview, gainers, climbers])
+}
+
+/// Players who went up in a stat, the biggest climb first
+fn biggest_climbs(changes: &[PlayerChange]) -> Vec<PlayerChange> {
+    let climb = |c: &PlayerChange| match c.ranks {
+        (Some(before), Some(after)) => before as i64 - after as i64,
+        // New players climbed from below the last rank
+        (None, Some(after)) => changes.len() as i64 + 1 - after as i64,
+        _ => 0,
+    };
+
+    let mut climbs = changes.iter().filter(|c| climb(c) > 0).cloned().collect::<Vec<PlayerChange>>();
+    climbs.sort_by_key(|c| -climb(c));
+
+    climbs
+}
@@ -0,0 +1,233 @@
+use crate::derived::StatRef;
+
+use serde::Deserialize;
+use std::{ collections::HashMap, convert::TryFrom };
+
+// Stat types that can be used in expressions, with underscores instead of spaces
+const STAT_TYPES: &[&str] = &[
+    "killed", "mined", "broken", "dropped", "picked_up", "crafted", "used", "killed_by", "custom", "derived",
+];
+
+/// A formula computing a value from stats, like `mined.diamond_ore*10 - custom.deaths*50`.
+/// Stats are written `stat_type.stat_name`, with underscores instead of spaces
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Expression {
+    Number(f64),
+    Stat(StatRef),
+    Negate(Box<Expression>),
+    Binary(Box<Expression>, Operator, Box<Expression>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    Dot,
+    Operator(Operator),
+    OpenParenthesis,
+    CloseParenthesis,
+}
+
+impl Expression {
+    /// The stats used by the expression, without duplicates
+    pub fn stats(&self) -> Vec<StatRef> {
+        let mut stats = vec![];
+        self.collect_stats(&mut stats);
+        stats
+    }
+
+    fn collect_stats(&self, stats: &mut Vec<StatRef>) {
+        match self {
+            Expression::Number(_) => {},
+            Expression::Stat(stat) => if !stats.contains(stat) {
+                stats.push(stat.clone());
+            },
+            Expression::Negate(expression) => expression.collect_stats(stats),
+            Expression::Binary(left, _, right) => {
+                left.collect_stats(stats);
+                right.collect_stats(stats);
+            },
+        }
+    }
+
+    /// Compute the expression with the given stat values, missing stats count as 0.
+    /// Returns None on a division by 0
+    pub fn evaluate(&self, values: &HashMap<StatRef, f64>) -> Option<f64> {
+        match self {
+            Expression::Number(n) => Some(*n),
+            Expression::Stat(stat) => Some(values.get(stat).copied().unwrap_or(0.0)),
+            Expression::Negate(expression) => expression.evaluate(values).map(|v| -v),
+            Expression::Binary(left, operator, right) => {
+                let left = left.evaluate(values)?;
+                let right = right.evaluate(values)?;
+
+                match operator {
+                    Operator::Add => Some(left + right),
+                    Operator::Subtract => Some(left - right),
+                    Operator::Multiply => Some(left * right),
+                    Operator::Divide => if right == 0.0 { None } else { Some(left / right) },
+                }
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for Expression {
+    type Error = String;
+
+    fn try_from(source: String) -> Result<Expression, String> {
+        parse_expression(&source)
+    }
+}
+
+pub fn parse_expression(source: &str) -> Result<Expression, String> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser { tokens, position: 0 };
+
+    let expression = parser.parse_sum()?;
+
+    match parser.tokens.get(parser.position) {
+        None => Ok(expression),
+        Some(token) => Err(format!("Unexpected {:?} in expression \"{}\"", token, source)),
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(i, c)) = chars.peek() {
+        match c {
+            ' ' | '\t' | '\n' => { chars.next(); },
+            '.' => { chars.next(); tokens.push(Token::Dot); },
+            '(' => { chars.next(); tokens.push(Token::OpenParenthesis); },
+            ')' => { chars.next(); tokens.push(Token::CloseParenthesis); },
+            '+' => { chars.next(); tokens.push(Token::Operator(Operator::Add)); },
+            '-' => { chars.next(); tokens.push(Token::Operator(Operator::Subtract)); },
+            '*' => { chars.next(); tokens.push(Token::Operator(Operator::Multiply)); },
+            '/' => { chars.next(); tokens.push(Token::Operator(Operator::Divide)); },
+            '0'..='9' => {
+                let mut number = String::new();
+                while let Some(&(_, c)) = chars.peek() {
+                    if c.is_ascii_digit() || c == '.' {
+                        number.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let number = number
+                    .parse()
+                    .map_err(|_| format!("Invalid number {} in expression \"{}\"", number, source))?;
+                tokens.push(Token::Number(number));
+            },
+            'a'..='z' | 'A'..='Z' | '_' => {
+                let mut identifier = String::new();
+                while let Some(&(_, c)) = chars.peek() {
+                    if c.is_ascii_alphanumeric() || c == '_' {
+                        identifier.push(c.to_ascii_lowercase());
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Identifier(identifier));
+            },
+            _ => return Err(format!("Unexpected character '{}' at position {} in expression \"{}\"", c, i, source)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn peek_operator(&self) -> Option<Operator> {
+        match self.tokens.get(self.position) {
+            Some(Token::Operator(operator)) => Some(*operator),
+            _ => None,
+        }
+    }
+
+    fn parse_sum(&mut self) -> Result<Expression, String> {
+        let mut expression = self.parse_product()?;
+
+        while let Some(operator @ (Operator::Add | Operator::Subtract)) = self.peek_operator() {
+            self.position += 1;
+            let right = self.parse_product()?;
+            expression = Expression::Binary(Box::new(expression), operator, Box::new(right));
+        }
+
+        Ok(expression)
+    }
+
+    fn parse_product(&mut self) -> Result<Expression, String> {
+        let mut expression = self.parse_unary()?;
+
+        while let Some(operator @ (Operator::Multiply | Operator::Divide)) = self.peek_operator() {
+            self.position += 1;
+            let right = self.parse_unary()?;
+            expression = Expression::Binary(Box::new(expression), operator, Box::new(right));
+        }
+
+        Ok(expression)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expression, String> {
+        if self.peek_operator() == Some(Operator::Subtract) {
+            self.position += 1;
+            return Ok(Expression::Negate(Box::new(self.parse_unary()?)));
+        }
+
+        self.parse_atom()
+    }
+
+    fn parse_atom(&mut self) -> Result<Expression, String> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expression::Number(n)),
+            Some(Token::OpenParenthesis) => {
+                let expression = self.parse_sum()?;
+                match self.next() {
+                    Some(Token::CloseParenthesis) => Ok(expression),
+                    _ => Err("Missing closing parenthesis in expression".to_string()),
+                }
+            },
+            Some(Token::Identifier(stat_type)) => {
+                if !STAT_TYPES.contains(&stat_type.as_str()) {
+                    return Err(format!("Unknown stat type {} in expression", stat_type));
+                }
+                if self.next() != Some(Token::Dot) {
+                    return Err(format!("Expected a '.' after the stat type {} in expression", stat_type));
+                }
+                match self.next() {
+                    Some(Token::Identifier(stat_name)) => Ok(Expression::Stat((
+                        stat_type.replace("_", " "),
+                        stat_name.replace("_", " "),
+                    ))),
+                    _ => Err(format!("Expected a stat name after {}. in expression", stat_type)),
+                }
+            },
+            Some(token) => Err(format!("Unexpected {:?} in expression", token)),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use crate::{
+    aliases::resolve_alias,
+    announcements::Holder,
+    bot_error::{ BotResult, BotError },
+    leaderboard::SortOrder,
+    scheduled_leaderboards::Leaderboard,
+    snapshots::stat_key,
+    stat::Stat,
+    suggestions::load_known_players,
+    utils::*,
+};
+
+use serenity::{
+    builder::CreateEmbed,
+    model::interactions::ApplicationCommandInteractionDataOption,
+};
+
+use serde::{ Deserialize, Serialize };
+use std::{ collections::HashMap, fs, path::Path };
+
+const HALL_OF_FAME_FILE: &str = "hall_of_fame.ron";
+// Seasons shown per stat when showing every stat, to stay under the length limit of fields
+const MAX_WINNERS_SHOWN: usize = 5;
+// Discord doesn't allow more fields than that in an embed
+const MAX_FIELDS: usize = 25;
+
+/// Winners and records of the finished seasons, with their usernames at the time
+/// so that it doesn't depend on the stats API
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HallOfFame {
+    #[serde(default)]
+    pub stats: Vec<StatHistory>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatHistory {
+    pub stat_type: String,
+    pub stat_name: String,
+    /// Winner of each season the stat was on the leaderboards, the oldest first
+    #[serde(default)]
+    pub winners: Vec<SeasonWinner>,
+    /// Best value reached in a single season
+    pub record: Option<SeasonWinner>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeasonWinner {
+    pub season: String,
+    pub holder: Holder,
+}
+
+pub struct HallOfFameCommandArgs {
+    pub stat_type: Option<String>,
+    pub stat_name: Option<String>,
+}
+
+pub fn load_hall_of_fame() -> BotResult<HallOfFame> {
+    if !Path::new(HALL_OF_FAME_FILE).exists() {
+        return Ok(HallOfFame::default());
+    }
+
+    Ok(ron::de::from_str(&fs::read_to_string(HALL_OF_FAME_FILE)?)?)
+}
+
+fn save_hall_of_fame(hall_of_fame: &HallOfFame) -> BotResult<()> {
+    fs::write(HALL_OF_FAME_FILE, ron::ser::to_string(hall_of_fame)?)?;
+
+    Ok(())
+}
+
+impl HallOfFame {
+    /// Add the winner of each leaderboard of a season, the first player of its standings.
+    /// Stats the season was already added to are left as they are
+    pub fn add_season(&mut self, season: &str, winners: Vec<(&Leaderboard, Stat)>) {
+        for (leaderboard, winner) in winners {
+            let winner = SeasonWinner {
+                season: season.to_string(),
+                holder: Holder { uuid: trim_uuid(&winner.uuid), username: winner.username, value: winner.value },
+            };
+
+            let index = match self.stats
+                .iter()
+                .position(|h| h.stat_type == leaderboard.stat_type && h.stat_name == leaderboard.stat_name)
+            {
+                Some(index) => index,
+                None => {
+                    self.stats.push(StatHistory {
+                        stat_type: leaderboard.stat_type.clone(),
+                        stat_name: leaderboard.stat_name.clone(),
+                        winners: vec![],
+                        record: None,
+                    });
+                    self.stats.len() - 1
+                },
+            };
+            let history = &mut self.stats[index];
+            if history.winners.iter().any(|w| w.season == season) {
+                continue;
+            }
+
+            let beats_record = history.record.as_ref().is_none_or(|record| match leaderboard.options.order {
+                SortOrder::Descending => winner.holder.value > record.holder.value,
+                SortOrder::Ascending => winner.holder.value < record.holder.value,
+            });
+            if beats_record {
+                history.record = Some(winner.clone());
+            }
+            history.winners.push(winner);
+        }
+    }
+}
+
+/// Save the winners of a season that just ended, from its final standings. Fails when a username
+/// can't be looked up nor is known, so that it can be tried again
+pub async fn record_season_winners(
+    season: &str,
+    standings: &HashMap<String, Vec<(String, f64)>>,
+    leaderboards: &[Leaderboard],
+) -> BotResult<()> {
+    let (leaderboards, mut winners): (Vec<&Leaderboard>, Vec<Stat>) = leaderboards
+        .iter()
+        .filter_map(|l| standings
+            .get(&stat_key(&l.stat_type, &l.stat_name))
+            .and_then(|standings| standings.first())
+            .map(|(uuid, value)| (l, Stat { success: true, uuid: uuid.clone(), username: String::new(), value: *value }))
+        )
+        .unzip();
+
+    let names = lookup_usernames_from_uuids(winners.iter().map(|w| untrim_uuid(trim_uuid(&w.uuid))).collect()).await;
+    let known_players = load_known_players()?;
+    for (winner, name) in winners.iter_mut().zip(names) {
+        winner.username = match name {
+            Ok(name) => name,
+            Err(e) => known_players
+                .iter()
+                .find(|(uuid, _)| trim_uuid(uuid) == trim_uuid(&winner.uuid))
+                .map(|(_, username)| username.clone())
+                .ok_or(e)?,
+        };
+    }
+
+    let mut hall_of_fame = load_hall_of_fame()?;
+    hall_of_fame.add_season(season, leaderboards.into_iter().zip(winners).collect());
+    save_hall_of_fame(&hall_of_fame)
+}
+
+pub fn get_hall_of_fame(args: &HallOfFameCommandArgs) -> BotResult<Vec<StatHistory>> {
+    let hall_of_fame = load_hall_of_fame()?;
+
+    match args.stat_type.as_ref().zip(args.stat_name.as_ref()) {
+        Some((stat_type, stat_name)) => {
+            let history = hall_of_fame.stats
+                .into_iter()
+                .find(|h| &h.stat_type == stat_type && &h.stat_name == stat_name)
+                .ok_or_else(|| BotError::Error(format!(
+                    "Error: {} wasn't on the leaderboards of any finished season",
+                    make_stat_title(&mut stat_type.clone(), &mut stat_name.clone())
+                )))?;
+
+            Ok(vec![history])
+        },
+        None if hall_of_fame.stats.is_empty() => Err(BotError::Error(
+            "Error: No season has ended yet".to_string()
+        )),
+        None => Ok(hall_of_fame.stats),
+    }
+}
+
+pub fn create_hall_of_fame_embed(
+    stats: Vec<StatHistory>,
+    embed: &mut CreateEmbed,
+) -> &mut CreateEmbed {
+    embed.title("Hall of fame").color((200, 255, 0));
+
+    let winner_line = |winner: &SeasonWinner, stat_name: &str| format!(
+        "**{}**: {} ({})", winner.season, winner.holder.username, format_stat_value(stat_name, winner.holder.value)
+    );
+
+    // A single stat shows all its seasons
+    if let [history] = stats.as_slice() {
+        let mut description = history.winners
+            .iter()
+            .rev()
+            .map(|winner| winner_line(winner, &history.stat_name))
+            .collect::<Vec<String>>()
+            .join("\n");
+        if let Some(ref record) = history.record {
+            description = format!("Record: {}\n\n{}", winner_line(record, &history.stat_name), description);
+            embed.thumbnail(format!("https://crafatar.com/avatars/{}", record.holder.uuid));
+        }
+
+        return embed
+            .title(format!(
+                "Hall of fame of {}",
+                make_stat_title(&mut history.stat_type.clone(), &mut history.stat_name.clone())
+            ))
+            .description(description);
+    }
+
+    for history in stats.iter().take(MAX_FIELDS) {
+        let mut lines = history.winners
+            .iter()
+            .rev()
+            .take(MAX_WINNERS_SHOWN)
+            .map(|winner| winner_line(winner, &history.stat_name))
+            .collect::<Vec<String>>();
+        if let Some(ref record) = history.record {
+            lines.insert(0, format!("Record: {}", winner_line(record, &history.stat_name)));
+        }
+
+        embed.field(
+            make_stat_title(&mut history.stat_type.clone(), &mut history.stat_name.clone()),
+            lines.join("\n"),
+            true
+        );
+    }
+
+    embed
+}
+
+pub fn parse_hall_of_fame_args(
+    args: &[ApplicationCommandInteractionDataOption]
+) -> HallOfFameCommandArgs {
+    let find_arg = |name: &str| args
+        .iter()
+        .find(|&x| x.name.as_str() == name)
+        .and_then(|data| data.value.as_ref())
+        .and_then(|value| value.as_str())
+        .map(|value| value.to_string());
+
+    match (find_arg("stat-type"), find_arg("stat-name")) {
+        (Some(stat_type), Some(stat_name)) => {
+            let (stat_type, stat_name) = resolve_alias(stat_type, stat_name);
+            HallOfFameCommandArgs { stat_type: Some(stat_type), stat_name: Some(stat_name) }
+        },
+        _ => HallOfFameCommandArgs { stat_type: None, stat_name: None },
+    }
+}
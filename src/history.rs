@@ -0,0 +1,146 @@
+use crate::{
+    aliases::resolve_alias,
+    bot_error::{ BotResult, BotError },
+    chart::{ render_line_chart, chart_bounds, Series, COLORS },
+    snapshots::{ snapshots_since, stat_key, Snapshot },
+    utils::*,
+};
+
+use serenity::{
+    builder::CreateEmbed,
+    model::interactions::ApplicationCommandInteractionDataOption,
+};
+
+const DAY: u64 = 60 * 60 * 24;
+const DEFAULT_DAYS: u64 = 30;
+pub const CHART_FILE_NAME: &str = "history.png";
+
+pub struct HistoryCommandArgs {
+    pub stat_type: String,
+    pub stat_name: String,
+    /// Defaults to the linked account of the user
+    pub player: Option<String>,
+    /// Players drawn over the first one
+    pub others: Vec<String>,
+    pub days: u64,
+}
+
+pub struct PlayerHistory {
+    pub player: String,
+    /// Values of the player by time of the snapshot
+    pub points: Vec<(u64, f64)>,
+}
+
+/// Values of a player in a stat over time. Snapshots taken before the stat was tracked are skipped
+pub fn stat_history(snapshots: &[Snapshot], key: &str, uuid: &str) -> Vec<(u64, f64)> {
+    snapshots
+        .iter()
+        .filter_map(|snapshot| snapshot.value(key, uuid).map(|value| (snapshot.taken_at, value)))
+        .collect()
+}
+
+pub async fn get_history(args: &HistoryCommandArgs, player: String) -> BotResult<Vec<PlayerHistory>> {
+    let key = stat_key(&args.stat_type, &args.stat_name);
+    let snapshots = snapshots_since(now().saturating_sub(args.days * DAY))?;
+
+    if !snapshots.iter().any(|snapshot| snapshot.values.contains_key(&key)) {
+        return Err(BotError::Error(format!(
+            "Error: There's no history of {}, only the stats of the leaderboards channel and of the milestones are recorded",
+            make_stat_title(&mut args.stat_type.clone(), &mut args.stat_name.clone())
+        )));
+    }
+
+    let mut histories = vec![];
+    for player in std::iter::once(player).chain(args.others.iter().cloned()) {
+        let uuid = get_uuid_from_username(player.as_str()).await?;
+        histories.push(PlayerHistory { points: stat_history(&snapshots, &key, &uuid), player });
+    }
+
+    Ok(histories)
+}
+
+/// Draw the chart of the histories, and get the values of its bottom and top lines
+pub fn create_history_chart(histories: &[PlayerHistory]) -> BotResult<(Vec<u8>, (f64, f64))> {
+    let series = histories
+        .iter()
+        .zip(COLORS.iter())
+        .map(|(history, &(color, _))| Series {
+            color,
+            points: history.points.iter().map(|&(time, value)| (time as f64, value)).collect(),
+        })
+        .collect::<Vec<Series>>();
+
+    let (_, y_bounds) = chart_bounds(&series);
+
+    Ok((render_line_chart(&series)?, y_bounds))
+}
+
+pub fn create_history_embed<'a>(
+    args: &HistoryCommandArgs,
+    histories: &[PlayerHistory],
+    (bottom, top): (f64, f64),
+    embed: &'a mut CreateEmbed,
+) -> &'a mut CreateEmbed {
+    let stat_title = make_stat_title(&mut args.stat_type.clone(), &mut args.stat_name.clone());
+
+    // The chart has no text, so the legend and the scale are in the embed
+    let legend = histories
+        .iter()
+        .zip(COLORS.iter())
+        .map(|(history, &(_, square))| match (history.points.first(), history.points.last()) {
+            (Some(&(_, first)), Some(&(_, last))) => format!(
+                "{} {}: {} (+{})",
+                square, history.player,
+                format_stat_value(&args.stat_name, last), format_stat_value(&args.stat_name, last - first)
+            ),
+            _ => format!("{} {}: no history", square, history.player),
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let (start, end) = histories
+        .iter()
+        .flat_map(|h| h.points.iter().map(|&(time, _)| time))
+        .fold((u64::MAX, 0), |(start, end), time| (start.min(time), end.max(time)));
+
+    embed
+        .title(match histories {
+            [history] => format!("{}'s {} over time", history.player, stat_title),
+            _ => format!("{} over time", stat_title),
+        })
+        .description(legend)
+        .field("Top line", format_stat_value(&args.stat_name, top), true)
+        .field("Bottom line", format_stat_value(&args.stat_name, bottom), true)
+        .field("Period", match start <= end {
+            true => format!("<t:{}:D> to <t:{}:D>", start, end),
+            false => "No snapshots".to_string(),
+        }, true)
+        .attachment(CHART_FILE_NAME)
+        .color((200, 255, 0))
+}
+
+pub fn parse_history_args(
+    args: &[ApplicationCommandInteractionDataOption]
+) -> HistoryCommandArgs {
+    let find_arg = |name: &str| args
+        .iter()
+        .find(|&x| x.name.as_str() == name)
+        .and_then(|data| data.value.as_ref())
+        .and_then(|value| value.as_str())
+        .map(|value| value.to_string());
+
+    let (stat_type, stat_name) = resolve_alias(
+        find_arg("stat-type").unwrap_or_default(), find_arg("stat-name").unwrap_or_default()
+    );
+    let others = (2..=COLORS.len())
+        .filter_map(|i| find_arg(&format!("player{}", i)))
+        .collect();
+    let days = args
+        .iter()
+        .find(|&x| x.name.as_str() == "days")
+        .and_then(|data| data.value.as_ref())
+        .and_then(|days| days.as_u64())
+        .unwrap_or(DEFAULT_DAYS);
+
+    HistoryCommandArgs { stat_type, stat_name, player: find_arg("player"), others, days }
+}
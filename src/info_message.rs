@@ -0,0 +1,98 @@
+use serenity::{
+    http::client::Http,
+    model::id::ChannelId,
+};
+
+use crate::{
+    bot_error::BotResult,
+    scheduled_leaderboards::{ LeaderboardMessage, INTERVAL },
+    seasons::season_name,
+    utils::*,
+};
+
+use std::{
+    fs,
+    path::Path,
+    sync::atomic::{ AtomicU64, Ordering },
+};
+
+const TEMPLATE_FILE: &str = "info_message.txt";
+// Set by the first update of the info message, when the bot starts
+static LAST_RESTART: AtomicU64 = AtomicU64::new(0);
+
+// Written to TEMPLATE_FILE the first time the bot starts without one
+const DEFAULT_TEMPLATE: &str = "\
+**Welcome to the stats leaderboards channel!**
+
+In this channel you can see leaderboards for various Minecraft statistics. \
+Those are the stats for the current EstillaCraft season: **{season}**.
+
+The leaderboards are updated every {refresh_interval}, but remember that your \
+stats are only updated when you log off the server!
+
+Currently displayed stats:
+{stats}
+
+The displayed stats can be changed and if you want a certain stat to be \
+displayed, you can ask Elzapat to update it.
+
+*Last restart: {last_restart}*
+";
+
+pub fn load_template() -> BotResult<String> {
+    if !Path::new(TEMPLATE_FILE).exists() {
+        fs::write(TEMPLATE_FILE, DEFAULT_TEMPLATE)?;
+    }
+
+    Ok(fs::read_to_string(TEMPLATE_FILE)?)
+}
+
+pub fn render_info_message(
+    template: &str,
+    messages: &[LeaderboardMessage],
+    season: &str,
+    last_restart: u64,
+) -> String {
+    let minutes = INTERVAL.as_secs() / 60;
+    let refresh_interval = match minutes {
+        1 => "minute".to_string(),
+        _ => format!("{} minutes", minutes),
+    };
+
+    let stats = messages
+        .iter()
+        .flat_map(|m| m.leaderboards.iter())
+        .map(|l| format!("• {}", make_stat_title(&mut l.stat_type.clone(), &mut l.stat_name.clone())))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    template
+        .replace("{refresh_interval}", &refresh_interval)
+        .replace("{stats}", &stats)
+        .replace("{season}", season)
+        // Discord renders this as a date in the reader's timezone
+        .replace("{last_restart}", &format!("<t:{}:f>", last_restart))
+}
+
+pub async fn update_info_message(
+    http: impl AsRef<Http>,
+    messages: &[LeaderboardMessage],
+) -> BotResult<()> {
+    let template = load_template()?;
+    let season = season_name();
+    let last_restart = match LAST_RESTART.load(Ordering::Relaxed) {
+        0 => {
+            LAST_RESTART.store(now(), Ordering::Relaxed);
+            now()
+        },
+        last_restart => last_restart,
+    };
+
+    let content = render_info_message(&template, messages, &season, last_restart);
+
+    ChannelId(LEADERBOARDS_CHANNEL)
+        .edit_message(http, INFO_MESSAGE, |message| message.content(content))
+        .await?;
+
+    Ok(())
+}
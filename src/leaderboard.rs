@@ -1,73 +1,439 @@
 use crate::{
+    aliases::resolve_alias,
+    derived::{ fetch_derived_ranking, DERIVED_STAT_TYPE },
     stat::Stat,
-    bot_error::BotResult,
+    privacy::load_privacy_list,
+    seasons::season_ranking,
+    suggestions::remember_players,
+    bot_error::{ BotResult, BotError },
     utils::*
 };
 
 use serenity::{
-    builder::CreateEmbed,
-    model::interactions::ApplicationCommandInteractionDataOption,
+    builder::{ CreateEmbed, CreateComponents },
+    model::interactions::{ ApplicationCommandInteractionDataOption, ButtonStyle },
+    prelude::TypeMapKey,
 };
 
-use num_format::{ Locale, ToFormattedString };
+use serde::Deserialize;
+use std::{
+    cmp::Ordering,
+    collections::{ HashMap, HashSet },
+    time::{ Duration, Instant },
+};
 
 pub struct LeaderboardCommandArgs {
     pub stat_type: String,
     pub stat_name: String,
     pub limit: Option<i64>,
+    pub player: Option<String>,
+    pub options: RankingOptions,
+    /// Shows the final standings of a finished season instead of the current values
+    pub season: Option<String>,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+pub enum SortOrder {
+    #[default]
+    Descending,
+    Ascending,
+}
 
-pub async fn get_leaderboard<S>(stat_type: S, stat_name: S, limit: Option<i64>) -> BotResult<Vec<Stat>>
-where
-    S: Into<String> + Clone
-{
-    let limit = match limit {
+/// How players are filtered and sorted on a leaderboard
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct RankingOptions {
+    #[serde(default)]
+    pub order: SortOrder,
+    /// Only players who played at least that long are ranked, so that people who joined once
+    /// don't top lowest-first leaderboards
+    pub min_play_hours: Option<f64>,
+    /// Only players with at least that value in the stat are ranked
+    pub min_value: Option<f64>,
+    /// Show every player tied with the last one shown instead of cutting the ties at the limit
+    #[serde(default)]
+    pub include_ties: bool,
+    /// Keep the players at 0, which are hidden by default
+    #[serde(default)]
+    pub show_zero: bool,
+}
+
+
+/// A full ranking shown by /leaderboard, kept so its pages can be browsed with buttons
+pub struct LeaderboardPages {
+    pub stat_type: String,
+    pub stat_name: String,
+    pub ranking: Vec<Stat>,
+    pub ranks: Vec<usize>,
+    /// Index in the ranking of the first player of each page
+    pub page_starts: Vec<usize>,
+    pub created_at: Instant,
+}
+
+/// Rankings of the /leaderboard commands still browsable, by id of the command interaction
+pub struct LeaderboardSessions;
+
+impl TypeMapKey for LeaderboardSessions {
+    type Value = HashMap<u64, LeaderboardPages>;
+}
+
+pub const PAGES_TIMEOUT: Duration = Duration::from_secs(60 * 10);
+// Number of players shown above and below the player of an "around me" leaderboard
+const AROUND_PLAYER_NEIGHBOURS: usize = 3;
+const PAGE_BUTTON_PREFIX: &str = "leaderboard-page";
+// Discord doesn't allow longer fields than what this many rows take
+const MAX_ROWS: usize = 25;
+// Discord doesn't allow more characters than that in a field, minus the closing backticks of the table
+const MAX_FIELD_LENGTH: usize = 1024 - 3;
+
+impl LeaderboardPages {
+    pub async fn new<S>(
+        stat_type: S,
+        stat_name: S,
+        limit: Option<i64>,
+        options: &RankingOptions,
+        season: Option<&str>,
+    ) -> BotResult<LeaderboardPages>
+    where
+        S: Into<String> + Clone
+    {
+        let ranking = match season {
+            Some(season) => {
+                // Archived standings are already filtered and sorted like their leaderboard was
+                if options.order != SortOrder::Descending || options.min_play_hours.is_some() {
+                    return Err(BotError::Error(
+                        "Error: The order and the minimum play time can't be changed for a finished season".to_string()
+                    ));
+                }
+
+                let privacy_list = load_privacy_list()?;
+                season_ranking(season, &stat_type.clone().into(), &stat_name.clone().into())?
+                    .into_iter()
+                    .filter(|s| !privacy_list.is_hidden(&s.uuid))
+                    .collect()
+            },
+            None => fetch_ranking(stat_type.clone(), stat_name.clone(), options).await?,
+        };
+
+        Ok(LeaderboardPages::from_ranking(stat_type, stat_name, ranking, limit, options.include_ties))
+    }
+
+    /// Split a sorted ranking into pages of the limit, ties at the end of a page are kept together if asked
+    pub fn from_ranking<S>(
+        stat_type: S,
+        stat_name: S,
+        ranking: Vec<Stat>,
+        limit: Option<i64>,
+        include_ties: bool,
+    ) -> LeaderboardPages
+    where
+        S: Into<String>
+    {
+        let page_size = clamp_limit(limit);
+
+        let mut page_starts = vec![0];
+        loop {
+            let start = *page_starts.last().unwrap();
+            let end = page_end(&ranking, start, page_size, include_ties);
+            if end >= ranking.len() {
+                break;
+            }
+            page_starts.push(end);
+        }
+
+        LeaderboardPages {
+            stat_type: stat_type.into(),
+            stat_name: stat_name.into(),
+            ranks: competition_ranks(&ranking),
+            ranking,
+            page_starts,
+            created_at: Instant::now(),
+        }
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.page_starts.len()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.created_at.elapsed() > PAGES_TIMEOUT
+    }
+
+    fn page_range(&self, page: usize) -> std::ops::Range<usize> {
+        let start = self.page_starts[page];
+        let end = self.page_starts.get(page + 1).copied().unwrap_or(self.ranking.len());
+
+        start..end
+    }
+
+    /// Get the ranks and the stats of a page as they are, usernames looked up so far included
+    pub fn page_stats(&self, page: usize) -> (Vec<usize>, Vec<Stat>) {
+        let range = self.page_range(page);
+
+        (self.ranks[range.clone()].to_vec(), self.ranking[range].to_vec())
+    }
+
+    /// Keep the usernames looked up for a page, so they're only looked up the first time
+    pub fn remember_usernames(&mut self, page: usize, stats: &[Stat]) {
+        let range = self.page_range(page);
+
+        for (kept, stat) in self.ranking[range].iter_mut().zip(stats.iter()) {
+            kept.username = stat.username.clone();
+        }
+    }
+
+    /// Get the ranks and the stats of a page, only looking up the usernames of its players the first time
+    pub async fn page(&mut self, page: usize) -> BotResult<(Vec<usize>, Vec<Stat>)> {
+        let (ranks, mut stats) = self.page_stats(page);
+
+        if stats.iter().any(|s| s.username.is_empty()) {
+            resolve_usernames(&mut stats).await?;
+            self.remember_usernames(page, &stats);
+        }
+
+        Ok((ranks, stats))
+    }
+
+    /// Get the ranks and the stats of a player and their neighbours, and the uuid of the player
+    pub async fn around_player(&mut self, player: &str) -> BotResult<(Vec<usize>, Vec<Stat>, String)> {
+        let uuid = get_uuid_from_username(player).await?;
+
+        let index = self.ranking
+            .iter()
+            .position(|s| trim_uuid(&s.uuid) == trim_uuid(&uuid))
+            .ok_or_else(|| BotError::Error(format!(
+                "{} isn't on the {} leaderboard",
+                player, make_stat_title(&mut self.stat_type.clone(), &mut self.stat_name.clone())
+            )))?;
+
+        let range = around_range(index, self.ranking.len());
+        let stats = &mut self.ranking[range.clone()];
+
+        resolve_usernames(stats).await?;
+
+        Ok((self.ranks[range].to_vec(), stats.to_vec(), self.ranking[index].uuid.clone()))
+    }
+}
+
+/// Indexes of the players shown around the player at an index of a ranking
+pub fn around_range(index: usize, ranking_length: usize) -> std::ops::Range<usize> {
+    let start = index.saturating_sub(AROUND_PLAYER_NEIGHBOURS);
+    let end = std::cmp::min(index + AROUND_PLAYER_NEIGHBOURS + 1, ranking_length);
+
+    start..end
+}
+
+fn clamp_limit(limit: Option<i64>) -> usize {
+    match limit {
         None => 10,
         Some(l) => match l {
             0 => 1,
-            1..=25 => l,
+            1..=25 => l as usize,
             _ => 25,
         }
+    }
+}
+
+/// Get the stat of every player, filtered and sorted with the options
+pub async fn fetch_ranking<S>(stat_type: S, stat_name: S, options: &RankingOptions) -> BotResult<Vec<Stat>>
+where
+    S: Into<String>
+{
+    let stats = fetch_unsorted_ranking(stat_type, stat_name).await?;
+
+    apply_ranking_options(stats, options).await
+}
+
+/// Get the stat of every player, before the options and the privacy list
+pub async fn fetch_unsorted_ranking<S>(stat_type: S, stat_name: S) -> BotResult<Vec<Stat>>
+where
+    S: Into<String>
+{
+    let stat_type = stat_type.into();
+    let stat_name = stat_name.into();
+
+    let mut stats = if stat_type == DERIVED_STAT_TYPE {
+        fetch_derived_ranking(&stat_name).await?
+    } else {
+        let request = format!(
+            "{}/api/v1/stats?uuid=all&stat_type={}&stat_name={}",
+            SERVER_ADDRESS, name_to_minecraft_id(stat_type), name_to_minecraft_id(stat_name)
+        );
+
+        reqwest::get(request)
+            .await?
+            .json::<Vec<Stat>>()
+            .await?
     };
 
-    let stat_type = name_to_minecraft_id(stat_type.into());
-    let stat_name = name_to_minecraft_id(stat_name.into());
+    stats.retain(|s| s.success);
 
+    Ok(stats)
+}
 
-    let request = format!(
-        "{}/api/v1/stats?uuid=all&stat_type={}&stat_name={}",
-        SERVER_ADDRESS, stat_type, stat_name
-    );
+pub async fn apply_ranking_options(stats: Vec<Stat>, options: &RankingOptions) -> BotResult<Vec<Stat>> {
+    let play_times = match options.min_play_hours {
+        Some(_) => fetch_unsorted_ranking("custom", "play time").await?,
+        None => vec![],
+    };
 
-    let mut stats = reqwest::get(request)
-        .await?
-        .json::<Vec<Stat>>()
-        .await?;
+    filter_ranking(stats, options, &play_times)
+}
 
-    stats.sort_by(|a, b| b.value.cmp(&a.value));
+/// Filter and sort a ranking with the options, the min_play_hours option is checked against the given play times
+pub fn filter_ranking(mut stats: Vec<Stat>, options: &RankingOptions, play_times: &[Stat]) -> BotResult<Vec<Stat>> {
+    let privacy_list = load_privacy_list()?;
+    stats.retain(|s| !privacy_list.is_hidden(&s.uuid));
 
-    if stats.len() > limit as usize {
-        stats.drain((limit as usize)..);
+    if !options.show_zero {
+        stats.retain(|s| s.value != 0.0);
     }
-    stats.drain_filter(|s| !s.success);
 
-    let mut uuids = vec![];
-    for s in stats.iter() {
-        uuids.push(s.uuid.clone());
+    if let Some(min_value) = options.min_value {
+        stats.retain(|s| s.value >= min_value);
     }
 
-    let names = get_usernames_from_uuids(uuids).await?;
+    if let Some(min_play_hours) = options.min_play_hours {
+        const TICKS_PER_HOUR: f64 = 20.0 * 60.0 * 60.0;
+
+        let regulars = play_times
+            .iter()
+            .filter(|s| s.value >= min_play_hours * TICKS_PER_HOUR)
+            .map(|s| trim_uuid(&s.uuid))
+            .collect::<HashSet<String>>();
 
-    for (i, s) in stats.iter_mut().enumerate() {
-        s.username = names[i].clone();
+        stats.retain(|s| regulars.contains(&trim_uuid(&s.uuid)));
     }
 
+    sort_ranking(&mut stats, options.order);
+
     Ok(stats)
 }
 
+/// Sort a ranking, tied players are sorted by uuid so they always show in the same order
+pub fn sort_ranking(stats: &mut [Stat], order: SortOrder) {
+    stats.sort_by(|a, b| {
+        let by_value = match order {
+            SortOrder::Descending => b.value.partial_cmp(&a.value),
+            SortOrder::Ascending => a.value.partial_cmp(&b.value),
+        };
+
+        by_value.unwrap_or(Ordering::Equal).then_with(|| a.uuid.cmp(&b.uuid))
+    });
+}
+
+/// Standard competition ranks of a sorted ranking: tied players share a rank and the
+/// following ranks are skipped (1, 2, 2, 4)
+pub fn competition_ranks(ranking: &[Stat]) -> Vec<usize> {
+    let mut ranks: Vec<usize> = Vec::with_capacity(ranking.len());
+
+    for (i, s) in ranking.iter().enumerate() {
+        let rank = match i {
+            0 => 1,
+            _ if s.value == ranking[i - 1].value => ranks[i - 1],
+            _ => i + 1,
+        };
+        ranks.push(rank);
+    }
+
+    ranks
+}
+
+/// Index after the last player shown when showing `limit` players from `start`
+fn page_end(ranking: &[Stat], start: usize, limit: usize, include_ties: bool) -> usize {
+    let mut end = std::cmp::min(start + limit, ranking.len());
+
+    if include_ties {
+        while end < ranking.len()
+            && end - start < MAX_ROWS
+            && ranking[end].value == ranking[end - 1].value
+        {
+            end += 1;
+        }
+    }
+
+    end
+}
+
+pub async fn resolve_usernames(stats: &mut [Stat]) -> BotResult<()> {
+    let uuids = stats.iter().map(|s| s.uuid.clone()).collect::<Vec<String>>();
+
+    let names = lookup_usernames_from_uuids(uuids).await;
+
+    // Failed lookups show their error in place of the username, but aren't remembered
+    let mut found = vec![];
+    for (s, name) in stats.iter_mut().zip(names) {
+        s.username = match name {
+            Ok(name) => {
+                found.push((s.uuid.clone(), name.clone()));
+                name
+            },
+            Err(e) => e.to_string(),
+        };
+    }
+
+    if let Err(e) = remember_players(&found) {
+        println!("Error saving known players: {:?}", e);
+    }
+
+    Ok(())
+}
+
+/// Keep the first players of a sorted ranking and get their ranks and usernames
+pub async fn top_of_ranking(
+    mut stats: Vec<Stat>,
+    limit: Option<i64>,
+    include_ties: bool,
+) -> BotResult<(Vec<usize>, Vec<Stat>)> {
+    let end = page_end(&stats, 0, clamp_limit(limit), include_ties);
+
+    stats.drain(end..);
+
+    resolve_usernames(&mut stats).await?;
+
+    Ok((competition_ranks(&stats), stats))
+}
+
+pub fn create_page_buttons(
+    components: &mut CreateComponents,
+    session_id: u64,
+    page: usize,
+    page_count: usize,
+) -> &mut CreateComponents {
+    components.create_action_row(|row| {
+        row
+            .create_button(|button| {
+                button
+                    .custom_id(format!("{}:{}:{}", PAGE_BUTTON_PREFIX, session_id, page.saturating_sub(1)))
+                    .label("Previous")
+                    .style(ButtonStyle::Secondary)
+                    .disabled(page == 0)
+            })
+            .create_button(|button| {
+                button
+                    .custom_id(format!("{}:{}:{}", PAGE_BUTTON_PREFIX, session_id, page + 1))
+                    .label("Next")
+                    .style(ButtonStyle::Secondary)
+                    .disabled(page + 1 >= page_count)
+            })
+    })
+}
+
+/// Get the session id and the requested page from the custom id of a page button
+pub fn parse_page_button(custom_id: &str) -> Option<(u64, usize)> {
+    let mut parts = custom_id.split(':');
+
+    if parts.next()? != PAGE_BUTTON_PREFIX {
+        return None;
+    }
+
+    let session_id = parts.next()?.parse().ok()?;
+    let page = parts.next()?.parse().ok()?;
+
+    Some((session_id, page))
+}
+
 pub fn parse_leaderboard_args(
-    args: &Vec<ApplicationCommandInteractionDataOption>
+    args: &[ApplicationCommandInteractionDataOption]
 ) -> LeaderboardCommandArgs {
     let mut args_iter = args.iter();
     let stat_type = args_iter
@@ -84,55 +450,64 @@ pub fn parse_leaderboard_args(
         .unwrap()
         .to_string()
         .replace("\"", "");
-    let limit = args_iter
+    let (stat_type, stat_name) = resolve_alias(stat_type, stat_name);
+    // Optional options can come in any order
+    let limit = args
+        .iter()
         .find(|&x| x.name.as_str() == "limit")
         .and_then(|data| data.value.as_ref())
         .and_then(|name| name.as_i64());
+    let player = args
+        .iter()
+        .find(|&x| x.name.as_str() == "player")
+        .and_then(|data| data.value.as_ref())
+        .and_then(|name| name.as_str())
+        .map(|name| name.to_string());
+    let order = match args
+        .iter()
+        .find(|&x| x.name.as_str() == "order")
+        .and_then(|data| data.value.as_ref())
+        .and_then(|order| order.as_str())
+    {
+        Some("lowest first") => SortOrder::Ascending,
+        _ => SortOrder::Descending,
+    };
+    let min_play_hours = args
+        .iter()
+        .find(|&x| x.name.as_str() == "min-play-hours")
+        .and_then(|data| data.value.as_ref())
+        .and_then(|hours| hours.as_f64());
 
-    LeaderboardCommandArgs { stat_type, stat_name, limit }
-}
-
-pub fn create_leaderboard_message<S>(
-    leaderboard: Vec<Stat>,
-    stat_type: S,
-    stat_name: S,
-) -> String
-where
-    S: Into<String> + Copy
-{
-    let stat_title = make_stat_title(&mut stat_type.into(), &mut stat_name.clone().into());
-
-    let mut ranks = (1..leaderboard.len() + 1)
-        .collect::<Vec<usize>>()
+    let include_ties = args
         .iter()
-        .map(|x| format!("{:<5}", x))
-        .collect::<Vec<String>>();
-    if ranks.is_empty() {
-        ranks.push("\u{200b}".to_string());
-    }
+        .find(|&x| x.name.as_str() == "include-ties")
+        .and_then(|data| data.value.as_ref())
+        .and_then(|include| include.as_bool())
+        .unwrap_or(false);
+    let season = args
+        .iter()
+        .find(|&x| x.name.as_str() == "season")
+        .and_then(|data| data.value.as_ref())
+        .and_then(|season| season.as_str())
+        .map(|season| season.to_string());
 
-    let mut names = leaderboard.iter().map(|s| {
-        s.username.clone()
-    }).collect::<Vec<String>>();
-    if names.is_empty() {
-        names.push("\u{200b}".to_string());
-    }
+    let options = RankingOptions { order, min_play_hours, include_ties, ..Default::default() };
 
-    let mut stats = leaderboard
-        .iter()
-        .map(|s| if stat_name.into() == "play time" {
-            minecraft_ticks_to_formatted_time(s.value)
-        } else {
-            s.value.to_formatted_string(&Locale::en)
-        })
-        .collect::<Vec<String>>();
-    if stats.is_empty() {
-        stats.push("\u{200b}".to_string());
-    }
+    LeaderboardCommandArgs { stat_type, stat_name, limit, player, options, season }
+}
 
+/// Lines of the table of a leaderboard embed, without the closing backticks
+fn create_leaderboard_table(
+    stat_title: &str,
+    ranks: &[String],
+    names: &[String],
+    stats: &[String],
+    sparklines: Option<&[String]>,
+) -> String {
     const RANKS_TITLE: &str = "Rank";
     const USERNAMES_TITLE: &str = "Username";
     const STATS_TITLE: &str = "Stat";
+    const TRENDS_TITLE: &str = "Trend";
 
     let longest_name = names.iter().fold(USERNAMES_TITLE.len(), |acc, name|
         if name.len() > acc { name.len() } else { acc }
@@ -148,35 +523,43 @@ where
         RANKS_TITLE, USERNAMES_TITLE, STATS_TITLE,
         name_len = longest_name, stat_len = longest_stat
     );
+    if sparklines.is_some() {
+        field_value = format!("{}  {}", field_value, TRENDS_TITLE);
+    }
 
     let line_len = field_value.len();
 
     field_value = format!(
-        "```{:^width$}\n\n{}\n",
+        "```ARM\n{:^width$}\n\n{}\n",
         stat_title, field_value,
         width = line_len
     );
     // let stat_title = format!("\u{200B}{:\u{2000}^width$}", stat_title, width = field_value.len());
 
     for i in 0..names.len() {
-        let line = format!(
+        let mut line = format!(
             "{} {:<name_len$} {:<stat_len$}",
             ranks[i], names[i], stats[i],
             name_len = longest_name + 1, stat_len = longest_stat
         );
+        if let Some(sparkline) = sparklines.and_then(|sparklines| sparklines.get(i)) {
+            line = format!("{}  {}", line, sparkline);
+        }
         field_value = format!(
-            "\n{}{:<width$}\n",
+            "{}{:<width$}\n",
             field_value, line,
             width = line_len
         );
     }
-    field_value = format!("{}```", field_value);
 
-    field_value.replace("``", "")
+    field_value
 }
 
 pub fn create_leaderboard_embed<'a, S>(
     leaderboard: Vec<Stat>,
+    ranks: Vec<usize>,
+    highlighted_uuid: Option<&str>,
+    sparklines: Option<Vec<String>>,
     stat_type: S,
     stat_name: S,
     embed: &'a mut CreateEmbed
@@ -184,7 +567,7 @@ pub fn create_leaderboard_embed<'a, S>(
 where
     S: Into<String> + Copy
 {
-    let stat_title = make_stat_title(&mut stat_type.into(), &mut stat_name.clone().into());
+    let stat_title = make_stat_title(&mut stat_type.into(), &mut stat_name.into());
     // embed.title(stat_title.clone());
 
     embed.color((200, 255, 0));
@@ -198,8 +581,7 @@ where
     //         _ => format!("`{}`\u{2800}", i),
     //     }
     // }).collect::<Vec<String>>();
-    let mut ranks = (1..leaderboard.len() + 1)
-        .collect::<Vec<usize>>()
+    let mut ranks = ranks
         .iter()
         .map(|x| format!("{:<5}", x))
         .collect::<Vec<String>>();
@@ -208,7 +590,11 @@ where
     }
 
     let mut names = leaderboard.iter().map(|s| {
-        s.username.clone()
+        if Some(s.uuid.as_str()) == highlighted_uuid {
+            format!("{} <<", s.username)
+        } else {
+            s.username.clone()
+        }
     }).collect::<Vec<String>>();
     if names.is_empty() {
         names.push("\u{200b}".to_string());
@@ -216,58 +602,26 @@ where
 
     let mut stats = leaderboard
         .iter()
-        .map(|s| if stat_name.into() == "play time" {
-            minecraft_ticks_to_formatted_time(s.value)
-        } else {
-            s.value.to_formatted_string(&Locale::en)
-        })
+        .map(|s| format_stat_value(&stat_name.into(), s.value))
         .collect::<Vec<String>>();
     if stats.is_empty() {
         stats.push("\u{200b}".to_string());
     }
 
-    const RANKS_TITLE: &str = "Rank";
-    const USERNAMES_TITLE: &str = "Username";
-    const STATS_TITLE: &str = "Stat";
-
-    let longest_name = names.iter().fold(USERNAMES_TITLE.len(), |acc, name|
-        if name.len() > acc { name.len() } else { acc }
-    );
-
-    let longest_stat = stats.iter().fold(STATS_TITLE.len(), |acc, stat|
-        if stat.len() > acc { stat.len() } else { acc }
-    );
-
-    // let mut field_value = String::from("```           \n");
-    let mut field_value = format!(
-        "{}  {:<name_len$}  {:<stat_len$}",
-        RANKS_TITLE, USERNAMES_TITLE, STATS_TITLE,
-        name_len = longest_name, stat_len = longest_stat
-    );
-
-    let line_len = field_value.len();
-
-    field_value = format!(
-        "```ARM\n{:^width$}\n\n{}\n",
-        stat_title, field_value,
-        width = line_len
-    );
-    // let stat_title = format!("\u{200B}{:\u{2000}^width$}", stat_title, width = field_value.len());
-
-    for i in 0..names.len() {
-        let line = format!(
-            "{} {:<name_len$} {:<stat_len$}",
-            ranks[i], names[i], stats[i],
-            name_len = longest_name + 1, stat_len = longest_stat
-        );
-        field_value = format!(
-            "{}{:<width$}\n",
-            field_value, line,
-            width = line_len
-        );
-    }
+    // The trend column is left out when it would push players out of the field
+    let mut field_value = match sparklines {
+        Some(sparklines) => {
+            let table = create_leaderboard_table(&stat_title, &ranks, &names, &stats, Some(&sparklines));
+            // Discord counts characters, and the blocks of the sparklines take several bytes
+            match table.chars().count() > MAX_FIELD_LENGTH {
+                true => create_leaderboard_table(&stat_title, &ranks, &names, &stats, None),
+                false => table,
+            }
+        },
+        None => create_leaderboard_table(&stat_title, &ranks, &names, &stats, None),
+    };
 
-    while field_value.len() > 1024 {
+    while field_value.chars().count() > MAX_FIELD_LENGTH {
         let mut lines = field_value.split('\n').collect::<Vec<&str>>();
         lines.pop();
         field_value = lines.join("\n");
@@ -0,0 +1,185 @@
+use crate::{
+    bot_error::{ BotResult, BotError },
+    suggestions::load_known_players,
+    utils::*,
+};
+
+use serenity::model::interactions::ApplicationCommandInteractionDataOption;
+
+use serde::{ Deserialize, Serialize };
+use std::{
+    collections::{ hash_map::RandomState, HashMap },
+    fs,
+    hash::{ BuildHasher, Hasher },
+    path::Path,
+};
+
+const LINKS_FILE: &str = "linked_accounts.ron";
+// Written by the /link command of the Minecraft server, with a "<uuid> <code>" line each time a player enters a code
+const ENTERED_CODES_FILE: &str = "link_codes.txt";
+// Time players have to enter their code in-game, in seconds
+const CODE_TIMEOUT: u64 = 60 * 30;
+
+/// Minecraft accounts of the Discord users, by Discord user id
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AccountLinks {
+    /// Trimmed uuids of the linked accounts
+    #[serde(default)]
+    pub linked: HashMap<u64, String>,
+    #[serde(default)]
+    pub pending: HashMap<u64, PendingLink>,
+}
+
+/// A link waiting for the player to enter its code in-game
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingLink {
+    pub uuid: String,
+    pub code: String,
+    pub created_at: u64,
+}
+
+pub struct LinkCommandArgs {
+    pub player: String,
+}
+
+pub fn load_account_links() -> BotResult<AccountLinks> {
+    if !Path::new(LINKS_FILE).exists() {
+        return Ok(AccountLinks::default());
+    }
+
+    Ok(ron::de::from_str(&fs::read_to_string(LINKS_FILE)?)?)
+}
+
+fn save_account_links(links: &AccountLinks) -> BotResult<()> {
+    fs::write(LINKS_FILE, ron::ser::to_string(links)?)?;
+
+    Ok(())
+}
+
+fn generate_code() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(now());
+
+    format!("{:06}", hasher.finish() % 1_000_000)
+}
+
+/// Get the code the player last entered in-game with /link, None when they didn't enter one yet
+fn fetch_entered_code(uuid: &str) -> BotResult<Option<String>> {
+    if !Path::new(ENTERED_CODES_FILE).exists() {
+        return Err(BotError::Error(format!(
+            "Error: Linking isn't available, the server doesn't write the entered codes to {} yet",
+            ENTERED_CODES_FILE
+        )));
+    }
+
+    Ok(latest_entered_code(&fs::read_to_string(ENTERED_CODES_FILE)?, uuid))
+}
+
+/// Get the latest code entered by a player, from the lines of the entered codes file
+pub fn latest_entered_code(entered_codes: &str, uuid: &str) -> Option<String> {
+    entered_codes
+        .lines()
+        .rev()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            Some((parts.next()?, parts.next()?))
+        })
+        .find(|&(entered_by, _)| trim_uuid(entered_by) == uuid)
+        .map(|(_, code)| code.to_string())
+}
+
+/// Start linking the account of a player, or finish it when its code was entered in-game,
+/// and get the message to answer with
+pub async fn run_link_command(args: &LinkCommandArgs, user_id: u64) -> BotResult<String> {
+    let uuid = trim_uuid(&get_uuid_from_username(&args.player).await?);
+    let mut links = load_account_links()?;
+
+    links.pending.retain(|_, pending| now() < pending.created_at + CODE_TIMEOUT);
+
+    let answer = match links.pending.get(&user_id) {
+        Some(pending) if pending.uuid == uuid => {
+            if fetch_entered_code(&uuid)?.as_ref() != Some(&pending.code) {
+                return Err(BotError::Error(format!(
+                    "Error: The code wasn't entered yet, type `/link {}` on the server and try again",
+                    pending.code
+                )));
+            }
+
+            links.pending.remove(&user_id);
+            links.linked.insert(user_id, uuid);
+            format!(
+                "Your account is now linked to {}, you can leave out the player in the commands to see your stats",
+                args.player
+            )
+        },
+        _ => {
+            let code = generate_code();
+            let answer = format!(
+                "Type `/link {}` on the server with {}, then use this command again to finish linking",
+                code, args.player
+            );
+            links.pending.insert(user_id, PendingLink { uuid, code, created_at: now() });
+            answer
+        },
+    };
+
+    save_account_links(&links)?;
+
+    Ok(answer)
+}
+
+pub fn run_unlink_command(user_id: u64) -> BotResult<String> {
+    let mut links = load_account_links()?;
+
+    let answer = match links.linked.remove(&user_id) {
+        Some(_) => "Your account isn't linked anymore".to_string(),
+        None => "Your account isn't linked to any player".to_string(),
+    };
+
+    save_account_links(&links)?;
+
+    Ok(answer)
+}
+
+/// Get the current username of the linked account of a user, if they linked one.
+/// The last known username is used when it can't be looked up
+pub async fn linked_player(user_id: u64) -> BotResult<Option<String>> {
+    let uuid = match load_account_links()?.linked.get(&user_id) {
+        Some(uuid) => uuid.clone(),
+        None => return Ok(None),
+    };
+
+    match lookup_usernames_from_uuids(vec![untrim_uuid(uuid.clone())]).await.remove(0) {
+        Ok(username) => Ok(Some(username)),
+        Err(e) => load_known_players()?
+            .into_iter()
+            .find(|(known, _)| trim_uuid(known) == uuid)
+            .map(|(_, username)| Some(username))
+            .ok_or(e),
+    }
+}
+
+/// Get the player given in a command, or the username of the linked account of the user
+pub async fn player_or_linked(player: Option<String>, user_id: u64) -> BotResult<String> {
+    if let Some(player) = player {
+        return Ok(player);
+    }
+
+    linked_player(user_id).await?.ok_or_else(|| BotError::Error(
+        "Error: Give a player, or link your account with `/link` to see your own stats".to_string()
+    ))
+}
+
+pub fn parse_link_args(
+    args: &[ApplicationCommandInteractionDataOption]
+) -> BotResult<LinkCommandArgs> {
+    let player = args
+        .iter()
+        .find(|&x| x.name.as_str() == "player")
+        .and_then(|data| data.value.as_ref())
+        .and_then(|player| player.as_str())
+        .ok_or_else(|| BotError::Error("Error: Give the player to link".to_string()))?
+        .to_string();
+
+    Ok(LinkCommandArgs { player })
+}
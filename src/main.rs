@@ -1,40 +1,82 @@
-#![feature(drain_filter)]
-
 pub mod bot_error;
 pub mod utils;
 pub mod stat;
+mod aliases;
+mod announcements;
 mod application_commands;
+mod catalog;
+mod chart;
+mod compare;
+mod derived;
+mod digest;
+mod expression;
+mod hall_of_fame;
+mod history;
+mod info_message;
 mod leaderboard;
+mod links;
+mod milestones;
+mod privacy;
+mod profile;
+mod rank;
 mod scheduled_leaderboards;
+mod seasons;
+mod server_stats;
+mod snapshots;
+mod subscriptions;
+mod suggestions;
 #[cfg(test)]
 mod tests;
 
-use std::env;
+use std::{ collections::HashMap, env };
 
 use serenity::{
     async_trait,
+    builder::CreateEmbed,
     http::client::Http,
     model::{
-        id::ChannelId,
         gateway::Ready,
         interactions::{
             ApplicationCommand,
             Interaction,
             InteractionResponseType,
             InteractionData,
+            InteractionApplicationCommandCallbackDataFlags,
         },
         event::ResumedEvent,
         channel::Message,
     },
     prelude::*,
+    utils::hashmap_to_json_map,
 };
 
+use serde_json::Value;
+
 use crate::{
     application_commands::create_application_commands,
-    leaderboard::{ parse_leaderboard_args, get_leaderboard, create_leaderboard_embed },
-    stat::{ get_stat, parse_stat_args, create_stat_embed },
+    compare::{ get_comparison, parse_compare_args, create_compare_embed },
+    bot_error::{ BotError, BotResult },
+    hall_of_fame::{ create_hall_of_fame_embed, get_hall_of_fame, parse_hall_of_fame_args },
+    history::{
+        create_history_chart, create_history_embed, get_history, parse_history_args, HistoryCommandArgs, CHART_FILE_NAME,
+    },
+    leaderboard::{
+        parse_leaderboard_args, create_leaderboard_embed, create_page_buttons, parse_page_button, resolve_usernames,
+        LeaderboardCommandArgs, LeaderboardPages, LeaderboardSessions,
+    },
+    links::{ linked_player, parse_link_args, player_or_linked, run_link_command, run_unlink_command },
+    privacy::{ parse_privacy_args, run_privacy_command },
+    profile::{ get_profile, parse_profile_args, create_profile_embed },
+    rank::{ get_ranks, parse_rank_args, create_rank_embed },
+    stat::{ get_stat, parse_stat_args, create_stat_embed, StatCommandArgs },
+    suggestions::{
+        create_suggestion_buttons, parse_suggestion_button, player_suggestions, stat_suggestions, suggestion_buttons,
+        Suggestion,
+    },
     scheduled_leaderboards::schedule_leaderboards,
-    utils::LEADERBOARDS_CHANNEL,
+    seasons::{ parse_season_args, run_season_command },
+    server_stats::{ create_server_stats_embed, get_server_stats, parse_server_stats_args },
+    subscriptions::{ parse_subscription_args, run_subscription_command, SubscriptionAction },
 };
 
 struct Handler;
@@ -46,48 +88,227 @@ impl EventHandler for Handler {
             match command.name.as_str() {
                 "stat" => {
                     let args = parse_stat_args(&command.options);
+                    respond_stat(&ctx, &interaction, args, InteractionResponseType::ChannelMessageWithSource).await;
+                },
+                "leaderboard" => {
+                    let args = parse_leaderboard_args(&command.options);
+                    respond_leaderboard(&ctx, &interaction, args, InteractionResponseType::ChannelMessageWithSource).await;
+                },
+                "rank" => {
+                    // Ranking the player on every leaderboard takes longer than Discord waits for a response
+                    if let Err(e) = interaction
+                        .create_interaction_response(&ctx.http, |response| {
+                            response.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+                        })
+                    .await {
+                        println!("Cannot respond to slash command: {}", e);
+                        return;
+                    }
+
+                    let ranks_result = match parse_rank_args(&command.options) {
+                        Ok(args) => match player_or_linked(args.player, interaction_user_id(&interaction)).await {
+                            Ok(player) => get_ranks(&player, args.stat).await.map(|ranks| (player, ranks)),
+                            Err(e) => Err(e),
+                        },
+                        Err(e) => Err(e),
+                    };
+
+                    if let Err(e) = interaction
+                        .edit_original_interaction_response(&ctx.http, |response| {
+                            match ranks_result {
+                                Ok((player, (uuid, ranks))) => response.create_embed(|e|
+                                    create_rank_embed(&player, &uuid, ranks, e)
+                                ),
+                                Err(e) => response.content(e),
+                            }
+                        })
+                    .await {
+                        println!("Cannot respond to slash command: {}", e)
+                    }
+                },
+                "compare" => {
+                    // Every stat of every player is fetched, which takes longer than Discord waits for a response
+                    if let Err(e) = interaction
+                        .create_interaction_response(&ctx.http, |response| {
+                            response.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+                        })
+                    .await {
+                        println!("Cannot respond to slash command: {}", e);
+                        return;
+                    }
+
+                    let compare_result = match parse_compare_args(&command.options) {
+                        Ok(args) => get_comparison(&args).await.map(|comparison| (args, comparison)),
+                        Err(e) => Err(e),
+                    };
+
+                    if let Err(e) = interaction
+                        .edit_original_interaction_response(&ctx.http, |response| {
+                            match compare_result {
+                                Ok((args, comparison)) => response.create_embed(|e|
+                                    create_compare_embed(&args, comparison, e)
+                                ),
+                                Err(e) => response.content(e),
+                            }
+                        })
+                    .await {
+                        println!("Cannot respond to slash command: {}", e)
+                    }
+                },
+                "profile" => {
+                    let args = parse_profile_args(&command.options);
+                    let player = player_or_linked(args.player, interaction_user_id(&interaction));
+
+                    respond_profile(&ctx, &interaction, player).await;
+                },
+                // User context menu command, the clicked member is the only resolved user
+                "Minecraft stats" => {
+                    let player = async {
+                        let user = command.resolved.users.values().next().ok_or_else(||
+                            BotError::Error("Error: Missing the user to show the stats of".to_string())
+                        )?;
+
+                        linked_player(user.id.0).await?.ok_or_else(|| BotError::Error(format!(
+                            "Error: {} didn't link their Minecraft account with `/link`", user.name
+                        )))
+                    };
 
-                    let stat_result = get_stat(&args.player, &args.stat_type, &args.stat_name).await;
+                    respond_profile(&ctx, &interaction, player).await;
+                },
+                "privacy" => {
+                    let privacy_result = match parse_privacy_args(&command.options) {
+                        Ok(args) => run_privacy_command(&args, interaction_user_id(&interaction), is_admin(&interaction)).await,
+                        Err(e) => Err(e),
+                    };
 
                     if let Err(e) = interaction
                         .create_interaction_response(&ctx.http, |response| {
                             response
                                 .kind(InteractionResponseType::ChannelMessageWithSource)
                                 .interaction_response_data(|message| {
-                                    match stat_result {
-                                        Ok(stat) => message.create_embed(|e| 
-                                            create_stat_embed(
-                                                stat.value, args.player, stat.uuid,
-                                                args.stat_type, args.stat_name, e
-                                            )
-                                        ),
+                                    match privacy_result {
+                                        Ok(answer) => message.content(answer),
                                         Err(e) => message.content(e),
                                     }
+                                    .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
                                 })
                         })
-                    .await
-                    {
+                    .await {
                         println!("Cannot respond to slash command: {}", e)
                     }
                 },
-                "leaderboard" => {
-                    let args = parse_leaderboard_args(&command.options);
+                "subscribe" | "unsubscribe" => {
+                    let action = match command.name.as_str() {
+                        "subscribe" => SubscriptionAction::Subscribe,
+                        _ => SubscriptionAction::Unsubscribe,
+                    };
+                    let args = parse_subscription_args(action, &command.options);
+                    let subscription_result = run_subscription_command(&args, interaction_user_id(&interaction));
 
-                    let leaderboard_result = get_leaderboard(&args.stat_type, &args.stat_name, args.limit).await;
+                    if let Err(e) = interaction
+                        .create_interaction_response(&ctx.http, |response| {
+                            response
+                                .kind(InteractionResponseType::ChannelMessageWithSource)
+                                .interaction_response_data(|message| {
+                                    match subscription_result {
+                                        Ok(answer) => message.content(answer),
+                                        Err(e) => message.content(e),
+                                    }
+                                    .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                                })
+                        })
+                    .await {
+                        println!("Cannot respond to slash command: {}", e)
+                    }
+                },
+                "link" | "unlink" => {
+                    let link_result = match command.name.as_str() {
+                        "link" => match parse_link_args(&command.options) {
+                            Ok(args) => run_link_command(&args, interaction_user_id(&interaction)).await,
+                            Err(e) => Err(e),
+                        },
+                        _ => run_unlink_command(interaction_user_id(&interaction)),
+                    };
 
                     if let Err(e) = interaction
                         .create_interaction_response(&ctx.http, |response| {
                             response
                                 .kind(InteractionResponseType::ChannelMessageWithSource)
                                 .interaction_response_data(|message| {
-                                    // println!("leaderboard_result = {:?}", leaderboard_result);
-                                    match leaderboard_result {
-                                        Ok(leaderboard) => message.create_embed(|e|
-                                            create_leaderboard_embed(
-                                                leaderboard, &args.stat_type, &args.stat_name, e
-                                            )
+                                    match link_result {
+                                        Ok(answer) => message.content(answer),
+                                        Err(e) => message.content(e),
+                                    }
+                                    .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                                })
+                        })
+                    .await {
+                        println!("Cannot respond to slash command: {}", e)
+                    }
+                },
+                "season" => {
+                    // Starting and ending a season fetch every leaderboard, which takes longer than Discord waits
+                    if let Err(e) = interaction
+                        .create_interaction_response(&ctx.http, |response| {
+                            response
+                                .kind(InteractionResponseType::DeferredChannelMessageWithSource)
+                                .interaction_response_data(|message| {
+                                    message.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                                })
+                        })
+                    .await {
+                        println!("Cannot respond to slash command: {}", e);
+                        return;
+                    }
+
+                    let season_result = match parse_season_args(&command.options) {
+                        Ok(action) => run_season_command(&ctx.http, action, is_admin(&interaction)).await,
+                        Err(e) => Err(e),
+                    };
+
+                    if let Err(e) = interaction
+                        .edit_original_interaction_response(&ctx.http, |response| {
+                            match season_result {
+                                Ok(answer) => response.content(answer),
+                                Err(e) => response.content(e),
+                            }
+                        })
+                    .await {
+                        println!("Cannot respond to slash command: {}", e)
+                    }
+                },
+                "halloffame" => {
+                    let hall_of_fame_result = get_hall_of_fame(&parse_hall_of_fame_args(&command.options));
+
+                    if let Err(e) = interaction
+                        .create_interaction_response(&ctx.http, |response| {
+                            response
+                                .kind(InteractionResponseType::ChannelMessageWithSource)
+                                .interaction_response_data(|message| {
+                                    match hall_of_fame_result {
+                                        Ok(stats) => message.create_embed(|e| create_hall_of_fame_embed(stats, e)),
+                                        Err(e) => message.content(e),
+                                    }
+                                })
+                        })
+                    .await {
+                        println!("Cannot respond to slash command: {}", e)
+                    }
+                },
+                "server-stats" => {
+                    let args = parse_server_stats_args(&command.options);
+                    let server_stats_result = get_server_stats(&args).await;
+
+                    if let Err(e) = interaction
+                        .create_interaction_response(&ctx.http, |response| {
+                            response
+                                .kind(InteractionResponseType::ChannelMessageWithSource)
+                                .interaction_response_data(|message| {
+                                    match server_stats_result {
+                                        Ok(stats) => message.create_embed(|e|
+                                            create_server_stats_embed(&args, stats, e)
                                         ),
-                                        Err(e) => message.content(e)
+                                        Err(e) => message.content(e),
                                     }
                                 })
                         })
@@ -95,9 +316,94 @@ impl EventHandler for Handler {
                         println!("Cannot respond to slash command: {}", e)
                     }
                 },
+                "history" => {
+                    respond_history(&ctx, &interaction, parse_history_args(&command.options)).await;
+                },
                 _ => {},//"not implemented :(".to_string(),
             };
         }
+
+        if let Some(InteractionData::MessageComponent(ref component)) = interaction.data {
+            match parse_suggestion_button(&component.custom_id) {
+                Some(Suggestion::Stat { player, stat_type, stat_name }) => {
+                    let args = StatCommandArgs { player: Some(player), stat_type, stat_name };
+                    respond_stat(&ctx, &interaction, args, InteractionResponseType::UpdateMessage).await;
+                },
+                Some(Suggestion::Leaderboard { stat_type, stat_name, limit, player, options, season }) => {
+                    let args = LeaderboardCommandArgs { stat_type, stat_name, limit, player, options, season };
+                    respond_leaderboard(&ctx, &interaction, args, InteractionResponseType::UpdateMessage).await;
+                },
+                None => {},
+            }
+
+            if let Some((session_id, page)) = parse_page_button(&component.custom_id) {
+                // Only what the page needs is copied out, so the map isn't locked while the usernames are fetched
+                let session = ctx.data.read().await
+                    .get::<LeaderboardSessions>()
+                    .unwrap()
+                    .get(&session_id)
+                    .map(|pages| match pages.is_expired() {
+                        true => Err("This leaderboard has expired, use `/leaderboard` again to browse it"),
+                        false => {
+                            let page = std::cmp::min(page, pages.page_count() - 1);
+                            let (ranks, stats) = pages.page_stats(page);
+
+                            Ok((pages.stat_type.clone(), pages.stat_name.clone(), pages.page_count(), page, ranks, stats))
+                        },
+                    })
+                    // Sessions are lost when the bot restarts
+                    .unwrap_or(Err("This leaderboard isn't available anymore, use `/leaderboard` again to browse it"));
+
+                let (stat_type, stat_name, page_count, page, ranks, mut stats) = match session {
+                    Ok(session) => session,
+                    Err(reason) => {
+                        if let Err(e) = interaction
+                            .create_interaction_response(&ctx.http, |response| {
+                                response
+                                    .kind(InteractionResponseType::UpdateMessage)
+                                    .interaction_response_data(|message| message.content(reason).components(|c| c))
+                            })
+                        .await {
+                            println!("Cannot respond to button: {}", e)
+                        }
+                        return;
+                    }
+                };
+
+                let page_result = match stats.iter().any(|s| s.username.is_empty()) {
+                    true => resolve_usernames(&mut stats).await,
+                    false => Ok(()),
+                };
+                if page_result.is_ok() {
+                    if let Some(pages) = ctx.data.write().await.get_mut::<LeaderboardSessions>().unwrap().get_mut(&session_id) {
+                        pages.remember_usernames(page, &stats);
+                    }
+                }
+                let page_result = page_result.map(|_| (ranks, stats));
+
+                if let Err(e) = interaction
+                    .create_interaction_response(&ctx.http, |response| {
+                        response
+                            .kind(InteractionResponseType::UpdateMessage)
+                            .interaction_response_data(|message| {
+                                match page_result {
+                                    Ok((ranks, stats)) => message
+                                        .create_embed(|e|
+                                            create_leaderboard_embed(
+                                                stats, ranks, None, None,
+                                                &stat_type, &stat_name, e
+                                            ).footer(|f| f.text(format!("Page {}/{}", page + 1, page_count)))
+                                        )
+                                        .components(|c| create_page_buttons(c, session_id, page, page_count)),
+                                    Err(e) => message.content(e),
+                                }
+                            })
+                    })
+                .await {
+                    println!("Cannot respond to button: {}", e)
+                }
+            }
+        }
     }
 
     async fn ready(&self, ctx: Context, ready: Ready) {
@@ -133,25 +439,6 @@ impl EventHandler for Handler {
             }
         }
         */
-
-        let mut info_msg: Message = ChannelId(LEADERBOARDS_CHANNEL)
-            .message(&ctx.http, 863385529831260221)
-            .await
-            .unwrap();
-        info_msg.edit(&ctx.http, |message| {
-            message.content("\n
-                **Welcome to the stats leaderboards channel!**\n\n\
-                In this channel you can see leaderboards for various Minecraft \
-                statistics. Those are the stats for the current EstillaCraft \
-                season.\n\n\
-                The leaderboards are updated every 5 minutes, but remember that your \
-                stats are only updated when you log off the server!\n\n\
-                The displayed stats can be changed and if you want a certain \
-                stat to be displayed, you can ask Elzapat to update it.\n\n\
-            ")
-        })
-        .await
-        .unwrap();
     }
 
     async fn resume(&self, _: Context, _: ResumedEvent) {
@@ -165,6 +452,268 @@ impl EventHandler for Handler {
     }
 }
 
+/// Id of the Discord user who used a command, in a guild or in DMs
+fn interaction_user_id(interaction: &Interaction) -> u64 {
+    match interaction.member {
+        Some(ref member) => member.user.id.0,
+        None => interaction.user.as_ref().map_or(0, |user| user.id.0),
+    }
+}
+
+/// Whether the user of a command can manage the guild it was used in
+fn is_admin(interaction: &Interaction) -> bool {
+    interaction.member
+        .as_ref()
+        .and_then(|member| member.permissions)
+        .is_some_and(|p| p.administrator() || p.manage_guild())
+}
+
+/// Respond to /profile or to the "Minecraft stats" user command, once the player is known
+async fn respond_profile(
+    ctx: &Context,
+    interaction: &Interaction,
+    player: impl std::future::Future<Output = BotResult<String>>,
+) {
+    // Getting all the stats of a profile takes longer than Discord waits for a response
+    if let Err(e) = interaction
+        .create_interaction_response(&ctx.http, |response| {
+            response.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+        })
+    .await {
+        println!("Cannot respond to slash command: {}", e);
+        return;
+    }
+
+    let profile_result = match player.await {
+        Ok(player) => get_profile(&player).await.map(|profile| (player, profile)),
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = interaction
+        .edit_original_interaction_response(&ctx.http, |response| {
+            match profile_result {
+                Ok((player, profile)) => response.create_embed(|e|
+                    create_profile_embed(&player, profile, e)
+                ),
+                Err(e) => response.content(e),
+            }
+        })
+    .await {
+        println!("Cannot respond to slash command: {}", e)
+    }
+}
+
+/// Respond to /history with the chart attached
+async fn respond_history(ctx: &Context, interaction: &Interaction, args: HistoryCommandArgs) {
+    // Reading the snapshots and drawing the chart can take longer than Discord waits for a response
+    if let Err(e) = interaction
+        .create_interaction_response(&ctx.http, |response| {
+            response.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+        })
+    .await {
+        println!("Cannot respond to slash command: {}", e);
+        return;
+    }
+
+    let history_result = match player_or_linked(args.player.clone(), interaction_user_id(interaction)).await {
+        Ok(player) => match get_history(&args, player).await {
+            Ok(histories) => create_history_chart(&histories).map(|chart| (histories, chart)),
+            Err(e) => Err(e),
+        },
+        Err(e) => Err(e),
+    };
+
+    let (histories, (png, bounds)) = match history_result {
+        Ok(history) => history,
+        Err(e) => {
+            if let Err(e) = interaction
+                .edit_original_interaction_response(&ctx.http, |response| response.content(e))
+            .await {
+                println!("Cannot respond to slash command: {}", e)
+            }
+            return;
+        },
+    };
+
+    let mut embed = CreateEmbed::default();
+    create_history_embed(&args, &histories, bounds, &mut embed);
+    let mut message = serde_json::Map::new();
+    message.insert("embeds".to_string(), Value::Array(vec![Value::Object(hashmap_to_json_map(embed.0))]));
+
+    // Followups of interactions are webhook messages, and serenity only sends files with the webhook request
+    if let Err(e) = ctx.http
+        .execute_webhook_with_files(
+            interaction.application_id.0, &interaction.token, true,
+            vec![(png.as_slice(), CHART_FILE_NAME)], message
+        )
+    .await {
+        println!("Cannot respond to slash command: {}", e)
+    }
+}
+
+/// Respond to /stat, or to a suggestion button of /stat when updating the message
+async fn respond_stat(
+    ctx: &Context,
+    interaction: &Interaction,
+    args: StatCommandArgs,
+    kind: InteractionResponseType,
+) {
+    let (player, stat_result) = match player_or_linked(args.player.clone(), interaction_user_id(interaction)).await {
+        Ok(player) => {
+            let stat_result = get_stat(&player, &args.stat_type, &args.stat_name).await;
+            (player, stat_result)
+        },
+        Err(e) => (String::new(), Err(e)),
+    };
+
+    // Typos in the player or the stat get buttons to run the command again corrected
+    let suggestions = suggestion_buttons(match stat_result {
+        Err(BotError::UnknownPlayer(_)) => player_suggestions(&player)
+            .into_iter()
+            .map(|player| (player.clone(), Suggestion::Stat {
+                player, stat_type: args.stat_type.clone(), stat_name: args.stat_name.clone(),
+            }))
+            .collect::<Vec<(String, Suggestion)>>(),
+        Ok(ref stat) if !stat.success => stat_suggestions(&args.stat_type, &args.stat_name)
+            .into_iter()
+            .map(|stat_name| (stat_name.clone(), Suggestion::Stat {
+                player: player.clone(), stat_type: args.stat_type.clone(), stat_name,
+            }))
+            .collect(),
+        _ => vec![],
+    });
+
+    if let Err(e) = interaction
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(kind)
+                .interaction_response_data(|message| {
+                    if !suggestions.is_empty() {
+                        let error = match stat_result {
+                            Err(ref e) => e.to_string(),
+                            Ok(_) => format!("Error: There is no stat named {}", args.stat_name),
+                        };
+                        return message
+                            .content(format!("{}. Did you mean:", error))
+                            .components(|c| create_suggestion_buttons(c, suggestions));
+                    }
+
+                    // Removes the suggestions when updating the message
+                    message.content("").components(|c| c);
+                    match stat_result {
+                        Ok(stat) => message.create_embed(|e|
+                            create_stat_embed(
+                                stat.value, player, stat.uuid,
+                                args.stat_type, args.stat_name, e
+                            )
+                        ),
+                        Err(e) => message.content(e),
+                    }
+                })
+        })
+    .await
+    {
+        println!("Cannot respond to slash command: {}", e)
+    }
+}
+
+/// Respond to /leaderboard, or to a suggestion button of /leaderboard when updating the message
+async fn respond_leaderboard(
+    ctx: &Context,
+    interaction: &Interaction,
+    args: LeaderboardCommandArgs,
+    kind: InteractionResponseType,
+) {
+    let session_id = interaction.id.0;
+
+    // Ranks shown, stats shown, highlighted player and all the pages
+    let pages = LeaderboardPages::new(
+        &args.stat_type, &args.stat_name, args.limit, &args.options, args.season.as_deref()
+    ).await;
+    let pages_result = match pages {
+        Ok(mut pages) => match args.player {
+            Some(ref player) => pages
+                .around_player(player)
+                .await
+                .map(|(ranks, stats, uuid)| (ranks, stats, Some(uuid), pages)),
+            None => pages.page(0).await.map(|(ranks, stats)| (ranks, stats, None, pages)),
+        },
+        Err(e) => Err(e),
+    };
+
+    // Typos in the player or the stat get buttons to run the command again corrected
+    let suggestions = suggestion_buttons(match pages_result {
+        Err(BotError::UnknownPlayer(ref player)) => player_suggestions(player)
+            .into_iter()
+            .map(|player| (player.clone(), Suggestion::Leaderboard {
+                stat_type: args.stat_type.clone(), stat_name: args.stat_name.clone(),
+                limit: args.limit, player: Some(player), options: args.options.clone(), season: args.season.clone(),
+            }))
+            .collect::<Vec<(String, Suggestion)>>(),
+        // Archived seasons only have the stats of their leaderboards, so there's nothing to suggest
+        Ok((_, _, _, ref pages)) if pages.ranking.is_empty() && args.season.is_none() => stat_suggestions(&args.stat_type, &args.stat_name)
+            .into_iter()
+            .map(|stat_name| (stat_name.clone(), Suggestion::Leaderboard {
+                stat_type: args.stat_type.clone(), stat_name,
+                limit: args.limit, player: args.player.clone(), options: args.options.clone(), season: args.season.clone(),
+            }))
+            .collect(),
+        _ => vec![],
+    });
+
+    if let Err(e) = interaction
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(kind)
+                .interaction_response_data(|message| {
+                    if !suggestions.is_empty() {
+                        let error = match pages_result {
+                            Err(ref e) => e.to_string(),
+                            Ok(_) => format!("Error: There is no stat named {}", args.stat_name),
+                        };
+                        return message
+                            .content(format!("{}. Did you mean:", error))
+                            .components(|c| create_suggestion_buttons(c, suggestions));
+                    }
+
+                    // Removes the suggestions when updating the message
+                    message.content("").components(|c| c);
+                    match pages_result {
+                        Ok((ref ranks, ref stats, ref uuid, ref pages)) => {
+                            let page_count = pages.page_count();
+                            message.create_embed(|e| {
+                                create_leaderboard_embed(
+                                    stats.clone(), ranks.clone(), uuid.as_deref(), None,
+                                    &args.stat_type, &args.stat_name, e
+                                );
+                                if uuid.is_none() {
+                                    e.footer(|f| f.text(format!("Page 1/{}", page_count)));
+                                }
+                                e
+                            });
+                            if uuid.is_none() && page_count > 1 {
+                                message.components(|c| create_page_buttons(c, session_id, 0, page_count));
+                            }
+                            message
+                        },
+                        Err(ref e) => message.content(e)
+                    }
+                })
+        })
+    .await {
+        println!("Cannot respond to slash command: {}", e)
+    }
+
+    if let Ok((_, _, None, pages)) = pages_result {
+        if pages.page_count() > 1 {
+            let mut data = ctx.data.write().await;
+            let sessions = data.get_mut::<LeaderboardSessions>().unwrap();
+            sessions.retain(|_, s| !s.is_expired());
+            sessions.insert(session_id, pages);
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
     dotenv::dotenv().expect("Failed to load .env");
@@ -185,6 +734,8 @@ async fn main() {
         .await
         .expect("Error creating client");
 
+    client.data.write().await.insert::<LeaderboardSessions>(HashMap::new());
+
     let _future = tokio::task::spawn(schedule_leaderboards(http));
 
     if let Err(e) = client.start().await {
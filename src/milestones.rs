@@ -0,0 +1,186 @@
+use crate::{
+    announcements::{ announcements_channel, announcements_ping },
+    bot_error::BotResult,
+    leaderboard::{ fetch_unsorted_ranking, resolve_usernames },
+    links::load_account_links,
+    privacy::load_privacy_list,
+    snapshots::{ stat_key, Snapshot },
+    stat::Stat,
+    utils::*,
+};
+
+use serenity::{
+    http::{ client::Http, CacheHttp },
+    model::id::ChannelId,
+};
+
+use serde::Deserialize;
+use std::{ collections::{ hash_map::Entry, HashMap }, fs, path::Path };
+
+const MILESTONES_FILE: &str = "milestones.ron";
+const REACHED_MILESTONES_FILE: &str = "reached_milestones.ron";
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Milestone {
+    pub stat_type: String,
+    pub stat_name: String,
+    /// In the unit of the stats API, ticks for times and centimeters for distances
+    pub value: f64,
+    /// Role given to the linked Discord account of the players reaching the milestone
+    #[serde(default)]
+    pub role_id: Option<u64>,
+}
+
+impl Milestone {
+    fn key(&self) -> String {
+        format!("{}:{}", stat_key(&self.stat_type, &self.stat_name), self.value)
+    }
+}
+
+pub fn load_milestones() -> BotResult<Vec<Milestone>> {
+    if !Path::new(MILESTONES_FILE).exists() {
+        return Ok(vec![]);
+    }
+
+    Ok(ron::de::from_str(&fs::read_to_string(MILESTONES_FILE)?)?)
+}
+
+/// Trimmed uuids of the players who reached each milestone, by milestone key
+fn load_reached_milestones() -> BotResult<HashMap<String, Vec<String>>> {
+    if !Path::new(REACHED_MILESTONES_FILE).exists() {
+        return Ok(HashMap::new());
+    }
+
+    Ok(ron::de::from_str(&fs::read_to_string(REACHED_MILESTONES_FILE)?)?)
+}
+
+/// Get the players who crossed a milestone since the previous snapshot, and weren't announced yet.
+/// Stats not in the previous snapshot have nothing to compare with and are skipped
+pub fn detect_milestones(
+    milestones: &[Milestone],
+    rankings: &HashMap<String, Vec<Stat>>,
+    previous: &Snapshot,
+    reached: &mut HashMap<String, Vec<String>>,
+) -> Vec<(Milestone, Stat)> {
+    let mut crossed = vec![];
+
+    for milestone in milestones.iter() {
+        let key = stat_key(&milestone.stat_type, &milestone.stat_name);
+        let ranking = match rankings.get(&key) {
+            Some(ranking) => ranking,
+            None => continue,
+        };
+        let reached = reached.entry(milestone.key()).or_default();
+
+        for s in ranking.iter().filter(|s| s.value >= milestone.value) {
+            let uuid = trim_uuid(&s.uuid);
+            let was_below = previous.value(&key, &uuid).is_some_and(|value| value < milestone.value);
+
+            if was_below && !reached.contains(&uuid) {
+                reached.push(uuid);
+                crossed.push((milestone.clone(), s.clone()));
+            }
+        }
+    }
+
+    crossed
+}
+
+/// Add the values of the milestone stats missing from the leaderboards ones
+pub async fn fetch_milestone_rankings(
+    milestones: &[Milestone],
+    values: &mut HashMap<String, Vec<Stat>>,
+) -> BotResult<()> {
+    for milestone in milestones.iter() {
+        if let Entry::Vacant(entry) = values.entry(stat_key(&milestone.stat_type, &milestone.stat_name)) {
+            entry.insert(fetch_unsorted_ranking(milestone.stat_type.as_str(), milestone.stat_name.as_str()).await?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Congratulate the players who crossed a milestone since the previous snapshot,
+/// and give its role to their linked Discord account. Players hidden from the leaderboards aren't congratulated
+pub async fn update_milestones(
+    http: impl AsRef<Http> + CacheHttp,
+    values: &mut HashMap<String, Vec<Stat>>,
+    previous: Option<&Snapshot>,
+) -> BotResult<()> {
+    let milestones = load_milestones()?;
+    fetch_milestone_rankings(&milestones, values).await?;
+
+    let previous = match previous {
+        Some(previous) => previous,
+        None => return Ok(()),
+    };
+
+    let privacy_list = load_privacy_list()?;
+    let shown = values
+        .iter()
+        .map(|(key, values)| (
+            key.clone(),
+            values.iter().filter(|s| !privacy_list.is_hidden(&s.uuid)).cloned().collect(),
+        ))
+        .collect::<HashMap<String, Vec<Stat>>>();
+
+    let mut reached = load_reached_milestones()?;
+    let crossed = detect_milestones(&milestones, &shown, previous, &mut reached);
+    fs::write(REACHED_MILESTONES_FILE, ron::ser::to_string(&reached)?)?;
+
+    if crossed.is_empty() {
+        return Ok(());
+    }
+
+    let (milestones, mut stats): (Vec<Milestone>, Vec<Stat>) = crossed.into_iter().unzip();
+    resolve_usernames(&mut stats).await?;
+
+    let links = load_account_links()?;
+    let guild_id = ChannelId(LEADERBOARDS_CHANNEL)
+        .to_channel(&http)
+        .await?
+        .guild()
+        .map(|channel| channel.guild_id);
+
+    for (milestone, s) in milestones.iter().zip(stats.iter()) {
+        let uuid = trim_uuid(&s.uuid);
+        let user_ids = links.linked
+            .iter()
+            .filter(|(_, linked)| **linked == uuid)
+            .map(|(&user_id, _)| user_id)
+            .collect::<Vec<u64>>();
+
+        if let (Some(role_id), Some(guild_id)) = (milestone.role_id, guild_id) {
+            for &user_id in user_ids.iter() {
+                if let Err(e) = http.as_ref().add_member_role(guild_id.0, user_id, role_id).await {
+                    println!("Error giving milestone role to {}: {:?}", user_id, e);
+                }
+            }
+        }
+
+        let channel = match announcements_channel() {
+            Some(channel) => channel,
+            None => continue,
+        };
+        let stat_title = make_stat_title(&mut milestone.stat_type.clone(), &mut milestone.stat_name.clone());
+
+        channel.send_message(&http, |message| {
+            message
+                .content(match announcements_ping() {
+                    true => user_ids.iter().map(|id| format!("<@{}>", id)).collect::<Vec<String>>().join(" "),
+                    false => String::new(),
+                })
+                .embed(|e| e
+                    .title(format!("Congratulations {}!", s.username))
+                    .description(format!(
+                        "{} reached {} in {}",
+                        s.username, format_stat_value(&milestone.stat_name, milestone.value), stat_title
+                    ))
+                    .thumbnail(format!("https://crafatar.com/avatars/{}", uuid))
+                    .color((200, 255, 0))
+                )
+        }).await?;
+    }
+
+    Ok(())
+}
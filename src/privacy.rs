@@ -0,0 +1,127 @@
+use crate::{
+    bot_error::{ BotResult, BotError },
+    links::load_account_links,
+    utils::{ get_uuid_from_username, trim_uuid },
+};
+
+use serenity::model::interactions::ApplicationCommandInteractionDataOption;
+
+use serde::{ Deserialize, Serialize };
+use std::{ collections::HashMap, fs, path::Path };
+
+const PRIVACY_FILE: &str = "privacy.ron";
+
+/// Players not shown on the leaderboards
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PrivacyList {
+    /// Players who asked to be hidden, by trimmed uuid, with the id of the Discord user who asked
+    #[serde(default)]
+    pub opted_out: HashMap<String, u64>,
+    /// Trimmed uuids of the players hidden by an admin, such as banned or alt accounts
+    #[serde(default)]
+    pub excluded: Vec<String>,
+}
+
+impl PrivacyList {
+    pub fn is_hidden(&self, uuid: &str) -> bool {
+        let uuid = trim_uuid(uuid);
+
+        self.opted_out.contains_key(&uuid) || self.excluded.contains(&uuid)
+    }
+}
+
+pub enum PrivacyAction {
+    Hide,
+    Show,
+    Exclude,
+    Include,
+}
+
+pub struct PrivacyCommandArgs {
+    pub action: PrivacyAction,
+    pub player: String,
+}
+
+pub fn load_privacy_list() -> BotResult<PrivacyList> {
+    if !Path::new(PRIVACY_FILE).exists() {
+        return Ok(PrivacyList::default());
+    }
+
+    Ok(ron::de::from_str(&fs::read_to_string(PRIVACY_FILE)?)?)
+}
+
+fn save_privacy_list(list: &PrivacyList) -> BotResult<()> {
+    fs::write(PRIVACY_FILE, ron::ser::to_string(list)?)?;
+
+    Ok(())
+}
+
+/// Update the privacy list for a /privacy command and get the message to answer with.
+/// Players can only hide their linked account and show again the accounts they hid or linked, admins can change any of them
+pub async fn run_privacy_command(args: &PrivacyCommandArgs, user_id: u64, is_admin: bool) -> BotResult<String> {
+    let uuid = trim_uuid(&get_uuid_from_username(&args.player).await?);
+    let mut list = load_privacy_list()?;
+    let is_owner = load_account_links()?.linked.get(&user_id).is_some_and(|linked| trim_uuid(linked) == uuid);
+
+    let answer = match args.action {
+        PrivacyAction::Hide if !is_owner && !is_admin => return Err(BotError::Error(format!(
+            "Error: {} isn't your linked account, use `/link` first or ask an admin", args.player
+        ))),
+        PrivacyAction::Hide => {
+            list.opted_out.entry(uuid).or_insert(user_id);
+            format!("{} won't be shown on the leaderboards anymore", args.player)
+        },
+        PrivacyAction::Show => match list.opted_out.get(&uuid) {
+            Some(&id) if id == user_id || is_owner || is_admin => {
+                list.opted_out.remove(&uuid);
+                format!("{} will be shown on the leaderboards again", args.player)
+            },
+            Some(_) => return Err(BotError::Error(format!(
+                "Error: {} was hidden by someone else, only them or an admin can show it again", args.player
+            ))),
+            None => format!("{} already is on the leaderboards", args.player),
+        },
+        PrivacyAction::Exclude | PrivacyAction::Include if !is_admin => return Err(BotError::Error(
+            "Error: Only admins can exclude players from the leaderboards".to_string()
+        )),
+        PrivacyAction::Exclude => {
+            if !list.excluded.contains(&uuid) {
+                list.excluded.push(uuid);
+            }
+            format!("{} is now excluded from the leaderboards", args.player)
+        },
+        PrivacyAction::Include => {
+            list.excluded.retain(|excluded| excluded != &uuid);
+            format!("{} isn't excluded from the leaderboards anymore", args.player)
+        },
+    };
+
+    save_privacy_list(&list)?;
+
+    Ok(answer)
+}
+
+pub fn parse_privacy_args(
+    args: &[ApplicationCommandInteractionDataOption]
+) -> BotResult<PrivacyCommandArgs> {
+    // The action is a subcommand, with the player as its only option
+    let subcommand = args
+        .first()
+        .ok_or_else(|| BotError::Error("Error: Choose what to do with the player".to_string()))?;
+
+    let action = match subcommand.name.as_str() {
+        "show" => PrivacyAction::Show,
+        "exclude" => PrivacyAction::Exclude,
+        "include" => PrivacyAction::Include,
+        _ => PrivacyAction::Hide,
+    };
+    let player = subcommand.options
+        .iter()
+        .find(|&x| x.name.as_str() == "player")
+        .and_then(|data| data.value.as_ref())
+        .and_then(|player| player.as_str())
+        .ok_or_else(|| BotError::Error("Error: Give the player to hide or show".to_string()))?
+        .to_string();
+
+    Ok(PrivacyCommandArgs { action, player })
+}
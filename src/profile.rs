@@ -0,0 +1,158 @@
+use crate::{
+    bot_error::BotResult,
+    stat::{ get_stats, Stat },
+    utils::*,
+};
+
+use serenity::{
+    builder::CreateEmbed,
+    model::interactions::ApplicationCommandInteractionDataOption,
+};
+
+use num_format::{ Locale, ToFormattedString };
+
+pub struct ProfileCommandArgs {
+    pub player: Option<String>,
+}
+
+pub struct Profile {
+    pub uuid: String,
+    pub play_time: u64,
+    pub deaths: u64,
+    pub mob_kills: u64,
+    // In centimeters
+    pub distance: u64,
+    pub mined: Vec<(String, u64)>,
+    pub killed: Vec<(String, u64)>,
+    pub killed_by: Vec<(String, u64)>,
+}
+
+const DISTANCE_STATS: &[&str] = &[
+    "walk one cm", "sprint one cm", "crouch one cm", "swim one cm", "walk on water one cm",
+    "walk under water one cm", "climb one cm", "fall one cm", "fly one cm", "aviate one cm",
+    "boat one cm", "horse one cm", "minecart one cm", "pig one cm", "strider one cm",
+];
+
+// The stats API can only give one stat at a time, so only those are looked at for the top stats
+const PROFILE_BLOCKS: &[&str] = &[
+    "stone", "deepslate", "dirt", "grass block", "sand", "gravel", "netherrack", "andesite",
+    "diorite", "granite", "tuff", "cobblestone", "oak log", "spruce log", "birch log",
+    "coal ore", "iron ore", "copper ore", "gold ore", "redstone ore", "lapis ore",
+    "diamond ore", "emerald ore", "deepslate iron ore", "deepslate diamond ore",
+    "nether quartz ore", "ancient debris", "obsidian", "sugar cane", "wheat",
+];
+
+const PROFILE_MOBS: &[&str] = &[
+    "zombie", "skeleton", "creeper", "spider", "cave spider", "enderman", "witch", "slime",
+    "drowned", "husk", "stray", "phantom", "pillager", "vindicator", "ravager", "blaze",
+    "ghast", "magma cube", "wither skeleton", "piglin", "zombified piglin", "hoglin",
+    "guardian", "elder guardian", "shulker", "silverfish", "cow", "pig", "sheep", "chicken",
+    "ender dragon", "wither", "player",
+];
+
+const TOP_COUNT: usize = 5;
+
+pub async fn get_profile(player: &str) -> BotResult<Profile> {
+    let mut stats = vec![
+        ("custom", "play time"),
+        ("custom", "deaths"),
+        ("custom", "mob kills"),
+    ];
+    stats.extend(DISTANCE_STATS.iter().map(|&name| ("custom", name)));
+    stats.extend(PROFILE_BLOCKS.iter().map(|&name| ("mined", name)));
+    stats.extend(PROFILE_MOBS.iter().map(|&name| ("killed", name)));
+    stats.extend(PROFILE_MOBS.iter().map(|&name| ("killed by", name)));
+
+    let (uuid, values) = get_stats(player, &stats).await?;
+    let mut values = values.into_iter();
+
+    let mut next_values = |count: usize| values
+        .by_ref()
+        .take(count)
+        .map(|s: Stat| if s.success { s.value as u64 } else { 0 })
+        .collect::<Vec<u64>>();
+
+    let general = next_values(3);
+    let distance = next_values(DISTANCE_STATS.len()).iter().sum();
+    let mined = top_values(PROFILE_BLOCKS, next_values(PROFILE_BLOCKS.len()));
+    let killed = top_values(PROFILE_MOBS, next_values(PROFILE_MOBS.len()));
+    let killed_by = top_values(PROFILE_MOBS, next_values(PROFILE_MOBS.len()));
+
+    Ok(Profile {
+        uuid,
+        play_time: general[0],
+        deaths: general[1],
+        mob_kills: general[2],
+        distance,
+        mined,
+        killed,
+        killed_by,
+    })
+}
+
+/// Get the names with the highest values, names at 0 left out
+pub fn top_values(names: &[&str], values: Vec<u64>) -> Vec<(String, u64)> {
+    let mut top = names
+        .iter()
+        .map(|name| name.to_string())
+        .zip(values)
+        .filter(|(_, value)| *value > 0)
+        .collect::<Vec<(String, u64)>>();
+
+    top.sort_by_key(|&(_, value)| std::cmp::Reverse(value));
+    top.truncate(TOP_COUNT);
+
+    top
+}
+
+pub fn parse_profile_args(
+    args: &[ApplicationCommandInteractionDataOption]
+) -> ProfileCommandArgs {
+    let player = args
+        .iter()
+        .find(|&x| x.name.as_str() == "player")
+        .and_then(|data| data.value.as_ref())
+        .and_then(|player| player.as_str())
+        .map(|player| player.to_string());
+
+    ProfileCommandArgs { player }
+}
+
+pub fn create_profile_embed<'a>(
+    player: &str,
+    profile: Profile,
+    embed: &'a mut CreateEmbed
+) -> &'a mut CreateEmbed {
+    embed.title(format!("{}'s profile", player));
+    embed.thumbnail(format!("https://crafatar.com/avatars/{}", profile.uuid));
+    embed.color((200, 255, 0));
+
+    embed.field("Play time", minecraft_ticks_to_formatted_time(profile.play_time), true);
+    embed.field("Deaths", profile.deaths.to_formatted_string(&Locale::en), true);
+    embed.field("Mob kills", profile.mob_kills.to_formatted_string(&Locale::en), true);
+    embed.field(
+        "Distance travelled",
+        format!("{} km", (profile.distance / 100_000).to_formatted_string(&Locale::en)),
+        true
+    );
+
+    let top_list = |top: Vec<(String, u64)>| if top.is_empty() {
+        "Nothing yet".to_string()
+    } else {
+        top
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, value))| format!(
+                "{}. {}: {}",
+                i + 1, make_ascii_titlecase(&mut name.clone()), value.to_formatted_string(&Locale::en)
+            ))
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+
+    embed.field("Most mined blocks", top_list(profile.mined), false);
+    embed.field("Most killed mobs", top_list(profile.killed), false);
+    embed.field("Top killers", top_list(profile.killed_by), false);
+
+    embed
+}
@@ -0,0 +1,133 @@
+use crate::{
+    aliases::resolve_alias,
+    bot_error::{ BotResult, BotError },
+    leaderboard::{ competition_ranks, RankingOptions },
+    scheduled_leaderboards::{ load_leaderboards, Leaderboard },
+    stat::Stat,
+    utils::*,
+};
+
+use serenity::{
+    builder::CreateEmbed,
+    model::interactions::ApplicationCommandInteractionDataOption,
+};
+
+use futures::future::try_join_all;
+
+pub struct RankCommandArgs {
+    pub player: Option<String>,
+    /// Type and name of the stat, every stat of the scheduled leaderboards when None
+    pub stat: Option<(String, String)>,
+}
+
+pub struct PlayerRank {
+    pub stat_type: String,
+    pub stat_name: String,
+    pub value: f64,
+    pub rank: usize,
+    pub player_count: usize,
+}
+
+/// Get the ranks of a player in the given stat, or in every stat of the scheduled leaderboards.
+/// Also returns the uuid of the player
+pub async fn get_ranks(
+    player: &str,
+    stat: Option<(String, String)>,
+) -> BotResult<(String, Vec<PlayerRank>)> {
+    let uuid = trim_uuid(&get_uuid_from_username(player).await?);
+
+    let leaderboards = match stat {
+        Some((stat_type, stat_name)) => vec![Leaderboard {
+            stat_type,
+            stat_name,
+            limit: None,
+            expression: None,
+            options: RankingOptions::default(),
+            announce: false,
+            sparkline: None,
+        }],
+        None => load_leaderboards()?
+            .into_iter()
+            .flat_map(|m| m.leaderboards.into_iter())
+            .collect(),
+    };
+
+    let ranks = try_join_all(leaderboards.into_iter().map(|leaderboard| {
+        let uuid = &uuid;
+        async move {
+            let ranking = leaderboard.fetch_ranking().await?;
+
+            BotResult::Ok(player_rank(leaderboard.stat_type, leaderboard.stat_name, &ranking, uuid))
+        }
+    })).await?;
+
+    // Stats the player doesn't have aren't shown
+    Ok((uuid, ranks.into_iter().flatten().collect()))
+}
+
+/// Get the rank of a player in a ranking, None when they aren't in it
+pub fn player_rank(stat_type: String, stat_name: String, ranking: &[Stat], uuid: &str) -> Option<PlayerRank> {
+    let index = ranking.iter().position(|s| trim_uuid(&s.uuid) == uuid)?;
+
+    Some(PlayerRank {
+        stat_type,
+        stat_name,
+        value: ranking[index].value,
+        rank: competition_ranks(ranking)[index],
+        player_count: ranking.len(),
+    })
+}
+
+pub fn parse_rank_args(
+    args: &[ApplicationCommandInteractionDataOption]
+) -> BotResult<RankCommandArgs> {
+    let find_arg = |name: &str| args
+        .iter()
+        .find(|&x| x.name.as_str() == name)
+        .and_then(|data| data.value.as_ref())
+        .and_then(|value| value.as_str())
+        .map(|value| value.to_string());
+
+    let stat = match (find_arg("stat-type"), find_arg("stat-name")) {
+        (Some(stat_type), Some(stat_name)) => Some(resolve_alias(stat_type, stat_name)),
+        (None, None) => None,
+        _ => return Err(BotError::Error(
+            "Error: Give both a stat type and a stat name, or neither to get the ranks in every leaderboard".to_string()
+        )),
+    };
+
+    Ok(RankCommandArgs { player: find_arg("player"), stat })
+}
+
+pub fn create_rank_embed<'a>(
+    player: &str,
+    uuid: &str,
+    ranks: Vec<PlayerRank>,
+    embed: &'a mut CreateEmbed
+) -> &'a mut CreateEmbed {
+    embed.title(format!("{}'s ranks", player));
+    embed.thumbnail(format!("https://crafatar.com/avatars/{}", uuid));
+    embed.color((200, 255, 0));
+
+    if ranks.is_empty() {
+        embed.description("This player isn't on any of the leaderboards yet");
+    }
+
+    // Discord doesn't allow more fields than that in an embed
+    for rank in ranks.into_iter().take(25) {
+        let stat_title = make_stat_title(&mut rank.stat_type.clone(), &mut rank.stat_name.clone());
+        let percentile = rank.rank as f64 / rank.player_count as f64 * 100.0;
+
+        embed.field(
+            stat_title,
+            format!(
+                "#{} of {} (top {:.0}%)\n{}",
+                rank.rank, rank.player_count, percentile.ceil(),
+                format_stat_value(&rank.stat_name, rank.value)
+            ),
+            true
+        );
+    }
+
+    embed
+}
@@ -1,4 +1,5 @@
 use serenity::{
+    builder::CreateEmbed,
     http::{
         CacheHttp,
         client::Http,
@@ -7,71 +8,422 @@ use serenity::{
         channel::Message,
         id::ChannelId,
     },
+    utils::hashmap_to_json_map,
 };
 
 use crate::{
-    bot_error::BotResult,
+    bot_error::{ BotResult, BotError },
+    derived::fetch_expression_ranking,
+    expression::Expression,
     leaderboard::{
-        get_leaderboard,
+        apply_ranking_options,
+        fetch_unsorted_ranking,
+        top_of_ranking,
         create_leaderboard_embed,
+        RankingOptions,
     },
-    utils::LEADERBOARDS_CHANNEL,
+    stat::Stat,
+    announcements::{ detect_announcements, load_announcements_state, post_announcements, save_announcements_state },
+    digest::{ fetch_digest_values, update_digest },
+    info_message::update_info_message,
+    milestones::update_milestones,
+    seasons::{ season_name, update_season },
+    snapshots::{ latest_snapshot, latest_snapshots, record_snapshot, stat_key, Snapshot },
+    subscriptions::update_subscriptions,
+    utils::{ sparkline, LEADERBOARDS_CHANNEL },
 };
 
-use serde::Deserialize;
-use std::fs;
+use serde::{ Deserialize, Serialize };
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+};
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Leaderboard {
+    pub stat_type: String,
+    pub stat_name: String,
+    pub limit: Option<i64>,
+    /// When set, the leaderboard ranks players by this expression and stat_name is only its title
+    pub expression: Option<Expression>,
+    #[serde(default)]
+    pub options: RankingOptions,
+    /// Post in the announcements channel when someone takes first place or breaks the season record
+    #[serde(default)]
+    pub announce: bool,
+    /// Show a trend column with the values of each player in that many of the latest snapshots
+    #[serde(default)]
+    pub sparkline: Option<usize>,
+}
+
+impl Leaderboard {
+    /// Get the value of every player, before the options of the leaderboard and the privacy list
+    pub async fn fetch_values(&self) -> BotResult<Vec<Stat>> {
+        match self.expression {
+            Some(ref expression) => fetch_expression_ranking(&self.stat_name, expression).await,
+            None => fetch_unsorted_ranking(self.stat_type.as_str(), self.stat_name.as_str()).await,
+        }
+    }
+
+    /// Get the full ranking of the leaderboard
+    pub async fn fetch_ranking(&self) -> BotResult<Vec<Stat>> {
+        apply_ranking_options(self.fetch_values().await?, &self.options).await
+    }
+}
+
+/// One message of the leaderboards channel, showing each of its leaderboards as an embed
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LeaderboardMessage {
+    pub message_id: u64,
+    pub leaderboards: Vec<Leaderboard>,
+}
 
-#[derive(Debug, Deserialize)]
-struct Leaderboard<'a> {
-    stat_type: &'a str,
-    stat_name: &'a str,
-    message_id: u64,
-    limit: Option<i64>,
+// Discord doesn't allow more embeds than that in a single message
+const MAX_EMBEDS_PER_MESSAGE: usize = 10;
+// Nor more characters than that in all the embeds of a message
+const MAX_EMBEDS_LENGTH: usize = 6000;
+const LEADERBOARDS_FILE: &str = "leaderboards.ron";
+const LEADERBOARD_MESSAGES_FILE: &str = "leaderboard_messages.ron";
+// Messages of the previous layout of one leaderboard per message, deleted when they aren't used anymore
+const FORMER_LEADERBOARD_MESSAGES: [u64; 12] = [
+    863385396758446114, 863385400893898752, 863385405213376524, 863385408715882517,
+    863385414315671562, 863385502768824331, 863385507185819689, 863385510482935829,
+    863385514055565342, 863385517637632041, 863385525107949618, 863385525129052170,
+];
+
+pub const INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 5);
+
+pub fn load_leaderboards() -> BotResult<Vec<LeaderboardMessage>> {
+    load_leaderboards_from(LEADERBOARDS_FILE)
 }
 
-const INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 5);
+pub fn load_leaderboards_from(path: &str) -> BotResult<Vec<LeaderboardMessage>> {
+    let messages = fs::read_to_string(path)?;
+    let messages: Vec<LeaderboardMessage> = ron::de::from_str(&messages)?;
+
+    if let Some(message) = messages.iter().find(|m| m.leaderboards.len() > MAX_EMBEDS_PER_MESSAGE) {
+        return Err(BotError::Error(format!(
+            "Message {} has {} leaderboards, the maximum is {}",
+            message.message_id, message.leaderboards.len(), MAX_EMBEDS_PER_MESSAGE
+        )));
+    }
+
+    Ok(messages)
+}
+
+/// Messages the bot posted leaderboards in, the only ones it deletes
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LeaderboardMessagesState {
+    /// Ids of the leaderboard messages of leaderboards.ron when it was last loaded
+    pub configured: Vec<u64>,
+    /// Messages sent after a leaderboard message for the embeds that don't fit in it, by id of the leaderboard message
+    #[serde(default)]
+    pub overflow: HashMap<u64, Vec<u64>>,
+}
+
+fn load_leaderboard_messages_state() -> BotResult<LeaderboardMessagesState> {
+    if !Path::new(LEADERBOARD_MESSAGES_FILE).exists() {
+        return Ok(LeaderboardMessagesState { configured: FORMER_LEADERBOARD_MESSAGES.to_vec(), overflow: HashMap::new() });
+    }
+
+    Ok(ron::de::from_str(&fs::read_to_string(LEADERBOARD_MESSAGES_FILE)?)?)
+}
+
+fn save_leaderboard_messages_state(state: &LeaderboardMessagesState) -> BotResult<()> {
+    fs::write(LEADERBOARD_MESSAGES_FILE, ron::ser::to_string(state)?)?;
+
+    Ok(())
+}
+
+/// Characters of an embed counted by Discord towards the limit of a message
+pub fn embed_length(embed: &Value) -> usize {
+    let text = |value: Option<&Value>| value
+        .and_then(|value| value.as_str())
+        .map_or(0, |value| value.chars().count());
+
+    text(embed.get("title"))
+        + text(embed.get("description"))
+        + text(embed.get("footer").and_then(|footer| footer.get("text")))
+        + text(embed.get("author").and_then(|author| author.get("name")))
+        + embed
+            .get("fields")
+            .and_then(|fields| fields.as_array())
+            .map_or(0, |fields| fields.iter().map(|f| text(f.get("name")) + text(f.get("value"))).sum())
+}
+
+/// Split the embeds of a leaderboard message into as few messages as Discord accepts, keeping their order
+pub fn split_embeds(embeds: Vec<Value>) -> Vec<Vec<Value>> {
+    let mut messages: Vec<Vec<Value>> = vec![];
+    let mut length = 0;
+
+    for embed in embeds {
+        let embed_length = embed_length(&embed);
+
+        match messages.last_mut() {
+            Some(message) if message.len() < MAX_EMBEDS_PER_MESSAGE && length + embed_length <= MAX_EMBEDS_LENGTH => {
+                message.push(embed);
+                length += embed_length;
+            },
+            _ => {
+                messages.push(vec![embed]);
+                length = embed_length;
+            },
+        }
+    }
+
+    messages
+}
+
+/// Ids of the recorded leaderboard and overflow messages that the leaderboards don't use anymore
+pub fn unused_leaderboard_messages(state: &LeaderboardMessagesState, messages: &[LeaderboardMessage]) -> Vec<u64> {
+    let used = |id: &u64| messages.iter().any(|m| m.message_id == *id);
+
+    state.configured
+        .iter()
+        .filter(|id| !used(id))
+        .copied()
+        .chain(state.overflow
+            .iter()
+            .filter(|(id, _)| !used(id))
+            .flat_map(|(_, extra)| extra.iter().copied())
+        )
+        .collect()
+}
+
+/// Delete the leaderboard messages removed from leaderboards.ron and their overflow messages,
+/// like the ones of the previous layout of one leaderboard per message
+async fn clean_up_leaderboards_channel(
+    http: impl AsRef<Http> + CacheHttp,
+    messages: &[LeaderboardMessage],
+) -> BotResult<()> {
+    let mut state = load_leaderboard_messages_state()?;
+    let channel = ChannelId(LEADERBOARDS_CHANNEL);
+
+    for id in unused_leaderboard_messages(&state, messages) {
+        // Already deleted by hand when it fails
+        if let Err(e) = channel.delete_message(&http, id).await {
+            println!("Error deleting leaderboard message {}: {:?}", id, e);
+        }
+    }
+
+    state.configured = messages.iter().map(|m| m.message_id).collect();
+    state.overflow.retain(|id, _| messages.iter().any(|m| m.message_id == *id));
+    save_leaderboard_messages_state(&state)
+}
 
 pub async fn schedule_leaderboards(http: impl AsRef<Http> + CacheHttp + 'static) -> BotResult<()> {
-    let leaderboards = fs::read_to_string("leaderboards.ron")?;
-    let leaderboards: Vec<Leaderboard> = ron::de::from_str(&leaderboards)?;
+    let mut leaderboards = load_leaderboards()?;
 
-    // Update leaderboards every ten minutes
+    if let Err(e) = update_info_message(&http, &leaderboards).await {
+        println!("Error updating info message: {:?}", e);
+    }
+    if let Err(e) = clean_up_leaderboards_channel(&http, &leaderboards).await {
+        println!("Error cleaning up the leaderboards channel: {:?}", e);
+    }
+
+    // Update leaderboards every five minutes
     let mut interval_timer = tokio::time::interval(INTERVAL);
 
     loop {
         interval_timer.tick().await;
-        if let Err(e) = update_leaderboards(&http, &leaderboards).await {
+
+        // Pick up edits to leaderboards.ron without a restart
+        match load_leaderboards() {
+            Ok(reloaded) if reloaded != leaderboards => {
+                leaderboards = reloaded;
+                if let Err(e) = update_info_message(&http, &leaderboards).await {
+                    println!("Error updating info message: {:?}", e);
+                }
+                if let Err(e) = clean_up_leaderboards_channel(&http, &leaderboards).await {
+                    println!("Error cleaning up the leaderboards channel: {:?}", e);
+                }
+            },
+            Ok(_) => {},
+            Err(e) => println!("Error reloading leaderboards, keeping the previous ones: {:?}", e),
+        }
+
+        // The rest still runs with the values that could be fetched
+        let (rankings, mut values) = update_leaderboards(&http, &leaderboards).await.unwrap_or_else(|e| {
             println!("Error updating scoreboards: {:?}", e);
+            (HashMap::new(), HashMap::new())
+        });
+
+        // Milestones are crossed since the latest snapshot, so they're checked before taking a new one
+        let latest = latest_snapshot().unwrap_or_else(|e| {
+            println!("Error loading the latest snapshot: {:?}", e);
+            None
+        });
+        if let Err(e) = update_milestones(&http, &mut values, latest.as_ref()).await {
+            println!("Error updating milestones: {:?}", e);
+        }
+        if let Err(e) = fetch_digest_values(&mut values).await {
+            println!("Error fetching the play time: {:?}", e);
+        }
+        if let Err(e) = update_subscriptions(&http, &rankings).await {
+            println!("Error notifying subscribers: {:?}", e);
+        }
+        if let Err(e) = record_snapshot(&Snapshot::new(&values), latest.as_ref()) {
+            println!("Error saving snapshot: {:?}", e);
+        }
+        if let Err(e) = update_digest(&http, &leaderboards).await {
+            println!("Error posting the weekly digest: {:?}", e);
+        }
+        if let Err(e) = update_season(&http).await {
+            println!("Error ending the season: {:?}", e);
         }
     }
 }
 
+/// Get the values of the leaderboards and the play time of every player by stat key, before the
+/// options of the leaderboards and the privacy list, without updating their messages
+pub async fn fetch_all_values(messages: &[LeaderboardMessage]) -> BotResult<HashMap<String, Vec<Stat>>> {
+    let mut values = HashMap::new();
+
+    for leaderboard in messages.iter().flat_map(|m| m.leaderboards.iter()) {
+        values.insert(stat_key(&leaderboard.stat_type, &leaderboard.stat_name), leaderboard.fetch_values().await?);
+    }
+    fetch_digest_values(&mut values).await?;
+
+    Ok(values)
+}
+
+/// Update the leaderboards messages, and get the full rankings of the leaderboards and the values
+/// they were ranked from, by stat key
 async fn update_leaderboards(
     http: impl AsRef<Http> + CacheHttp,
-    leaderboards: &Vec<Leaderboard<'_>>
-) -> BotResult<()> {
-    for leaderboard in leaderboards.iter() {
+    messages: &[LeaderboardMessage]
+) -> BotResult<(HashMap<String, Vec<Stat>>, HashMap<String, Vec<Stat>>)> {
+    let mut announcements_state = load_announcements_state(&season_name())?;
+    let mut announcements = vec![];
+    let mut rankings = HashMap::new();
+    let mut all_values = HashMap::new();
+    let mut state = load_leaderboard_messages_state()?;
+
+    // Loaded once for every leaderboard with a trend column
+    let sparkline_length = messages
+        .iter()
+        .flat_map(|m| m.leaderboards.iter())
+        .filter_map(|l| l.sparkline)
+        .max();
+    // Without them the leaderboards are still updated, only without their trend column
+    let snapshots = match sparkline_length.map(latest_snapshots) {
+        Some(Ok(snapshots)) => snapshots,
+        Some(Err(e)) => {
+            println!("Error loading the snapshots of the sparklines: {:?}", e);
+            vec![]
+        },
+        None => vec![],
+    };
+
+    for leaderboard_message in messages.iter() {
         let http = &http;
 
-        let mut msg: Message = ChannelId(LEADERBOARDS_CHANNEL)
-            .message(http, leaderboard.message_id)
-            .await?;
-
-        let stats = get_leaderboard(
-            leaderboard.stat_type, leaderboard.stat_name, leaderboard.limit
-        ).await?;
-
-        msg.edit(http, |message|
-            message
-                .content("")
-                .embed(|e|
-                    create_leaderboard_embed(
-                        stats, leaderboard.stat_type,
-                        leaderboard.stat_name, e
-                    )
-                )
-        ).await?;
+        // A message that can't be updated doesn't stop the others
+        let updated: BotResult<()> = async {
+            let mut embeds = vec![];
+            for leaderboard in leaderboard_message.leaderboards.iter() {
+                let key = stat_key(&leaderboard.stat_type, &leaderboard.stat_name);
+                let values = leaderboard.fetch_values().await?;
+                let ranking = apply_ranking_options(values.clone(), &leaderboard.options).await?;
+                all_values.insert(key.clone(), values);
+                rankings.insert(key.clone(), ranking.clone());
+
+                let (ranks, stats) = top_of_ranking(ranking, leaderboard.limit, leaderboard.options.include_ties).await?;
+
+                if leaderboard.announce {
+                    announcements.extend(detect_announcements(
+                        leaderboard, &all_values[&key], &rankings[&key], &stats, &mut announcements_state
+                    ));
+                }
+
+                let sparklines = leaderboard.sparkline.filter(|_| !snapshots.is_empty()).map(|length| {
+                    let snapshots = &snapshots[snapshots.len().saturating_sub(length)..];
+
+                    stats
+                        .iter()
+                        .map(|s| sparkline(&snapshots
+                            .iter()
+                            .filter_map(|snapshot| snapshot.value(&key, &s.uuid))
+                            .collect::<Vec<f64>>()
+                        ))
+                        .collect::<Vec<String>>()
+                });
+
+                let mut embed = CreateEmbed::default();
+                create_leaderboard_embed(
+                    stats, ranks, None, sparklines, leaderboard.stat_type.as_str(),
+                    leaderboard.stat_name.as_str(), &mut embed
+                );
+                embeds.push(Value::Object(hashmap_to_json_map(embed.0)));
+            }
+
+            // Fetched after the values, which are kept even when the message can't be edited
+            let mut msg: Message = ChannelId(LEADERBOARDS_CHANNEL)
+                .message(http, leaderboard_message.message_id)
+                .await?;
+
+            // Embeds that don't fit in the message go in the messages sent after it
+            let mut parts = split_embeds(embeds).into_iter();
+
+            // EditMessage only knows about a single embed, so the array is set by hand
+            msg.edit(http, |message| {
+                message.content("");
+                message.0.insert("embeds", Value::Array(parts.next().unwrap_or_default()));
+                message
+            }).await?;
+
+            let channel = ChannelId(LEADERBOARDS_CHANNEL);
+            let parts = parts.collect::<Vec<Vec<Value>>>();
+            let mut extra = state.overflow.remove(&leaderboard_message.message_id).unwrap_or_default();
+
+            for &id in extra.iter().skip(parts.len()) {
+                if let Err(e) = channel.delete_message(http, id).await {
+                    println!("Error deleting leaderboard message {}: {:?}", id, e);
+                }
+            }
+            extra.truncate(parts.len());
+
+            for (i, part) in parts.into_iter().enumerate() {
+                let edited = match extra.get(i) {
+                    Some(&id) => channel.edit_message(http, id, |message| {
+                        message.0.insert("embeds", Value::Array(part.clone()));
+                        message
+                    }).await.is_ok(),
+                    None => false,
+                };
+
+                // Sent again when it was never sent or was deleted by hand
+                if !edited {
+                    let sent = channel.send_message(http, |message| {
+                        message.0.insert("embeds", Value::Array(part));
+                        message
+                    }).await?;
+
+                    match extra.get_mut(i) {
+                        Some(id) => *id = sent.id.0,
+                        None => extra.push(sent.id.0),
+                    }
+                    // Saved right away so that a later error doesn't send it again
+                    state.overflow.insert(leaderboard_message.message_id, extra.clone());
+                    save_leaderboard_messages_state(&state)?;
+                }
+            }
+            state.overflow.insert(leaderboard_message.message_id, extra);
+
+            Ok(())
+        }.await;
+
+        if let Err(e) = updated {
+            println!("Error updating leaderboard message {}: {:?}", leaderboard_message.message_id, e);
+        }
     }
 
-    Ok(())
+    state.overflow.retain(|_, extra| !extra.is_empty());
+    save_leaderboard_messages_state(&state)?;
+    save_announcements_state(&announcements_state)?;
+    post_announcements(&http, announcements).await?;
+
+    Ok((rankings, all_values))
 }
@@ -0,0 +1,313 @@
+use crate::{
+    announcements::announcements_channel,
+    bot_error::{ BotResult, BotError },
+    hall_of_fame::record_season_winners,
+    info_message::update_info_message,
+    leaderboard::{ create_leaderboard_embed, filter_ranking, top_of_ranking, RankingOptions, SortOrder },
+    scheduled_leaderboards::{ fetch_all_values, load_leaderboards, Leaderboard },
+    snapshots::{ load_snapshot, save_snapshot, stat_key, Snapshot },
+    stat::Stat,
+    utils::*,
+};
+
+use serenity::{
+    builder::CreateEmbed,
+    http::{ client::Http, CacheHttp },
+    model::interactions::ApplicationCommandInteractionDataOption,
+    utils::hashmap_to_json_map,
+};
+
+use serde::{ Deserialize, Serialize };
+use serde_json::Value;
+use std::{ collections::HashMap, env, fs, path::Path };
+
+const SEASONS_FILE: &str = "seasons.ron";
+const DAY: u64 = 60 * 60 * 24;
+// Discord doesn't allow more embeds than that in a single message
+const MAX_EMBEDS_PER_MESSAGE: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Season {
+    pub name: String,
+    pub start: u64,
+    /// Planned end of a running season, or actual end of a finished one
+    pub end: Option<u64>,
+    #[serde(default)]
+    pub finished: bool,
+    /// Time the snapshot the season values are counted from was taken at
+    pub baseline: u64,
+    /// Final values of the players gained during the season, sorted like their leaderboard, by stat key
+    #[serde(default)]
+    pub standings: HashMap<String, Vec<(String, f64)>>,
+}
+
+pub enum SeasonAction {
+    Start { name: String, days: Option<u64> },
+    End,
+    List,
+}
+
+pub fn load_seasons() -> BotResult<Vec<Season>> {
+    if !Path::new(SEASONS_FILE).exists() {
+        return Ok(vec![]);
+    }
+
+    Ok(ron::de::from_str(&fs::read_to_string(SEASONS_FILE)?)?)
+}
+
+fn save_seasons(seasons: &[Season]) -> BotResult<()> {
+    fs::write(SEASONS_FILE, ron::ser::to_string(&seasons)?)?;
+
+    Ok(())
+}
+
+pub fn current_season() -> BotResult<Option<Season>> {
+    Ok(load_seasons()?.into_iter().find(|s| !s.finished))
+}
+
+/// Name of the current season, or of the SEASON_NAME environment variable when no season was started
+pub fn season_name() -> String {
+    current_season()
+        .ok()
+        .flatten()
+        .map(|season| season.name)
+        .or_else(|| env::var("SEASON_NAME").ok())
+        .unwrap_or_else(|| "unnamed".to_string())
+}
+
+/// Archived ranking of a stat in a finished season
+pub fn season_ranking(season: &str, stat_type: &str, stat_name: &str) -> BotResult<Vec<Stat>> {
+    let season = load_seasons()?
+        .into_iter()
+        .find(|s| s.finished && s.name.eq_ignore_ascii_case(season))
+        .ok_or_else(|| BotError::Error(format!("Error: There is no finished season named {}", season)))?;
+
+    let standings = season.standings
+        .get(&stat_key(stat_type, stat_name))
+        .ok_or_else(|| BotError::Error(format!(
+            "Error: {} wasn't on the leaderboards during the {} season",
+            make_stat_title(&mut stat_type.to_string(), &mut stat_name.to_string()), season.name
+        )))?;
+
+    Ok(standings
+        .iter()
+        .map(|(uuid, value)| Stat { success: true, uuid: uuid.clone(), username: String::new(), value: *value })
+        .collect())
+}
+
+/// Values gained during the season by every player of each leaderboard, filtered and sorted like the
+/// leaderboard, from the values of every player. The minimum play time counts the play time of the season
+pub fn season_standings(
+    leaderboards: &[Leaderboard],
+    values: &HashMap<String, Vec<Stat>>,
+    baseline: &Snapshot,
+) -> BotResult<HashMap<String, Vec<(String, f64)>>> {
+    let gains = |key: &str| values.get(key).map(|values| values
+        .iter()
+        .map(|s| Stat { value: s.value - baseline.value(key, &s.uuid).unwrap_or(0.0), ..s.clone() })
+        .collect::<Vec<Stat>>()
+    );
+    let play_times = gains(&stat_key("custom", "play time")).unwrap_or_default();
+
+    let mut standings = HashMap::new();
+    for leaderboard in leaderboards.iter() {
+        let key = stat_key(&leaderboard.stat_type, &leaderboard.stat_name);
+        let ranking = match gains(&key) {
+            Some(ranking) => ranking,
+            None => continue,
+        };
+
+        // Players who gained nothing are the best of the ascending leaderboards, like the fewest deaths
+        let options = RankingOptions {
+            show_zero: leaderboard.options.show_zero || leaderboard.options.order == SortOrder::Ascending,
+            ..leaderboard.options.clone()
+        };
+        let ranking = filter_ranking(ranking, &options, &play_times)?;
+
+        standings.insert(key, ranking.into_iter().map(|s| (s.uuid, s.value)).collect());
+    }
+
+    Ok(standings)
+}
+
+/// Start, end or list the seasons, and get the message to answer with
+pub async fn run_season_command(
+    http: impl AsRef<Http> + CacheHttp,
+    action: SeasonAction,
+    is_admin: bool,
+) -> BotResult<String> {
+    let mut seasons = load_seasons()?;
+
+    match action {
+        SeasonAction::List => Ok(match seasons.is_empty() {
+            true => "No season was started yet".to_string(),
+            false => seasons
+                .iter()
+                .map(|s| match (s.finished, s.end) {
+                    (true, Some(end)) => format!("**{}**: <t:{}:D> to <t:{}:D>", s.name, s.start, end),
+                    (false, Some(end)) => format!("**{}**: since <t:{}:D>, ends <t:{}:R>", s.name, s.start, end),
+                    _ => format!("**{}**: since <t:{}:D>", s.name, s.start),
+                })
+                .collect::<Vec<String>>()
+                .join("\n"),
+        }),
+        _ if !is_admin => Err(BotError::Error("Error: Only admins can start and end seasons".to_string())),
+        SeasonAction::Start { name, days } => {
+            if let Some(current) = seasons.iter().find(|s| !s.finished) {
+                return Err(BotError::Error(format!("Error: The {} season has to end first", current.name)));
+            }
+            // Season names end up in the custom ids of the suggestion buttons, which are split at colons
+            if name.contains(':') {
+                return Err(BotError::Error("Error: Season names can't contain colons".to_string()));
+            }
+            if seasons.iter().any(|s| s.name.eq_ignore_ascii_case(&name)) {
+                return Err(BotError::Error(format!("Error: There already was a season named {}", name)));
+            }
+
+            let messages = load_leaderboards()?;
+            let baseline = Snapshot::new(&fetch_all_values(&messages).await?);
+            save_snapshot(&baseline)?;
+
+            seasons.push(Season {
+                name: name.clone(),
+                start: baseline.taken_at,
+                end: days.map(|days| baseline.taken_at + days * DAY),
+                finished: false,
+                baseline: baseline.taken_at,
+                standings: HashMap::new(),
+            });
+            save_seasons(&seasons)?;
+
+            if let Err(e) = update_info_message(&http, &messages).await {
+                println!("Error updating info message: {:?}", e);
+            }
+
+            Ok(format!("The {} season started!", name))
+        },
+        SeasonAction::End => {
+            let season = end_season(&http).await?;
+
+            Ok(format!("The {} season ended, its final standings were posted", season.name))
+        },
+    }
+}
+
+/// End the current season if its planned end passed
+pub async fn update_season(http: impl AsRef<Http> + CacheHttp) -> BotResult<()> {
+    let due = current_season()?
+        .and_then(|season| season.end)
+        .is_some_and(|end| now() >= end);
+
+    if due {
+        end_season(http).await?;
+    }
+
+    Ok(())
+}
+
+/// Archive the final standings of the current season, record its winners and post them.
+/// The season is only marked as finished once that's done, so it's tried again after an error
+async fn end_season(http: impl AsRef<Http> + CacheHttp) -> BotResult<Season> {
+    let mut seasons = load_seasons()?;
+    let index = seasons
+        .iter()
+        .position(|s| !s.finished)
+        .ok_or_else(|| BotError::Error("Error: No season is running".to_string()))?;
+
+    let messages = load_leaderboards()?;
+    let leaderboards = messages
+        .iter()
+        .flat_map(|m| m.leaderboards.iter().cloned())
+        .collect::<Vec<Leaderboard>>();
+
+    // The standings of the first try are kept, so that a retry records and posts the same ones
+    if seasons[index].standings.is_empty() {
+        let values = fetch_all_values(&messages).await?;
+        let baseline = load_snapshot(seasons[index].baseline)?;
+        seasons[index].standings = season_standings(&leaderboards, &values, &baseline)?;
+        seasons[index].end = Some(now());
+        save_seasons(&seasons)?;
+    }
+
+    record_season_winners(&seasons[index].name, &seasons[index].standings, &leaderboards).await?;
+    post_final_standings(&http, &seasons[index], &leaderboards).await?;
+
+    seasons[index].finished = true;
+    save_seasons(&seasons)?;
+
+    if let Err(e) = update_info_message(&http, &messages).await {
+        println!("Error updating info message: {:?}", e);
+    }
+
+    Ok(seasons.swap_remove(index))
+}
+
+async fn post_final_standings(
+    http: impl AsRef<Http> + CacheHttp,
+    season: &Season,
+    leaderboards: &[Leaderboard],
+) -> BotResult<()> {
+    let channel = match announcements_channel() {
+        Some(channel) => channel,
+        None => return Ok(()),
+    };
+
+    let mut embeds = vec![];
+    for leaderboard in leaderboards.iter() {
+        let standings = match season.standings.get(&stat_key(&leaderboard.stat_type, &leaderboard.stat_name)) {
+            Some(standings) => standings,
+            None => continue,
+        };
+        let ranking = standings
+            .iter()
+            .map(|(uuid, value)| Stat { success: true, uuid: uuid.clone(), username: String::new(), value: *value })
+            .collect::<Vec<Stat>>();
+        let (ranks, stats) = top_of_ranking(ranking, leaderboard.limit, leaderboard.options.include_ties).await?;
+
+        let mut embed = CreateEmbed::default();
+        create_leaderboard_embed(
+            stats, ranks, None, None, leaderboard.stat_type.as_str(), leaderboard.stat_name.as_str(), &mut embed
+        );
+        embeds.push(Value::Object(hashmap_to_json_map(embed.0)));
+    }
+
+    channel.say(&http, format!("**Final standings of the {} season**", season.name)).await?;
+    for chunk in embeds.chunks(MAX_EMBEDS_PER_MESSAGE) {
+        channel.send_message(&http, |message| {
+            message.0.insert("embeds", Value::Array(chunk.to_vec()));
+            message
+        }).await?;
+    }
+
+    Ok(())
+}
+
+pub fn parse_season_args(
+    args: &[ApplicationCommandInteractionDataOption]
+) -> BotResult<SeasonAction> {
+    // The action is a subcommand, with its own options
+    let subcommand = args
+        .first()
+        .ok_or_else(|| BotError::Error("Error: Choose what to do with the seasons".to_string()))?;
+
+    Ok(match subcommand.name.as_str() {
+        "start" => {
+            let name = subcommand.options
+                .iter()
+                .find(|&x| x.name.as_str() == "name")
+                .and_then(|data| data.value.as_ref())
+                .and_then(|name| name.as_str())
+                .ok_or_else(|| BotError::Error("Error: Give the name of the season".to_string()))?
+                .to_string();
+            let days = subcommand.options
+                .iter()
+                .find(|&x| x.name.as_str() == "days")
+                .and_then(|data| data.value.as_ref())
+                .and_then(|days| days.as_u64());
+
+            SeasonAction::Start { name, days }
+        },
+        "end" => SeasonAction::End,
+        _ => SeasonAction::List,
+    })
+}
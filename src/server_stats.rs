@@ -0,0 +1,151 @@
+use crate::{
+    aliases::resolve_alias,
+    bot_error::{ BotResult, BotError },
+    derived::{ load_derived_stats, DerivedKind, DERIVED_STAT_TYPE },
+    leaderboard::fetch_unsorted_ranking,
+    utils::*,
+};
+
+use serenity::{
+    builder::CreateEmbed,
+    model::interactions::ApplicationCommandInteractionDataOption,
+};
+
+use num_format::{ Locale, ToFormattedString };
+
+const CM_PER_KM: f64 = 100_000.0;
+
+/// Distances to compare the distance stats to, in kilometers, the biggest first
+const LANDMARKS: &[(f64, &str)] = &[
+    (384_400.0, "trips to the Moon"),
+    (40_075.0, "trips around the Earth"),
+    (30_000.0, "walks from spawn to the world border"),
+    (21_196.0, "walks along the Great Wall of China"),
+    (42.195, "marathons"),
+];
+
+pub struct ServerStatsCommandArgs {
+    pub stat_type: String,
+    pub stat_name: String,
+}
+
+/// Totals of a stat over the players who have some of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerStats {
+    pub total: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub player_count: usize,
+}
+
+/// Sum, mean and median of the values, None without values
+pub fn summarize(values: &[f64]) -> Option<ServerStats> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let total = sorted.iter().sum::<f64>();
+    let middle = sorted.len() / 2;
+    let median = match sorted.len() % 2 {
+        0 => (sorted[middle - 1] + sorted[middle]) / 2.0,
+        _ => sorted[middle],
+    };
+
+    Some(ServerStats { total, mean: total / sorted.len() as f64, median, player_count: sorted.len() })
+}
+
+/// Whether the values of a stat are distances in centimeters, like the "one cm" stats and their sums
+pub fn is_distance_stat(stat_type: &str, stat_name: &str) -> bool {
+    let is_cm_stat = |stat_type: &str, stat_name: &str| stat_type == "custom" && stat_name.ends_with("one cm");
+
+    match stat_type {
+        DERIVED_STAT_TYPE => load_derived_stats()
+            .unwrap_or_default()
+            .into_iter()
+            .find(|d| d.name == stat_name)
+            .is_some_and(|d| match d.kind {
+                DerivedKind::Sum(ref stats) => stats.iter().all(|(t, n)| is_cm_stat(t, n)),
+                _ => false,
+            }),
+        _ => is_cm_stat(stat_type, stat_name),
+    }
+}
+
+/// Compare a distance in centimeters to the biggest landmark it goes past, or to a marathon
+pub fn distance_comparison(cm: f64) -> String {
+    let km = cm / CM_PER_KM;
+
+    match LANDMARKS.iter().find(|&&(length, _)| km >= length) {
+        Some(&(length, name)) => format!("That's {:.1} {}", km / length, name),
+        None => {
+            let (marathon, _) = LANDMARKS[LANDMARKS.len() - 1];
+            format!("That's {:.0}% of a marathon", km / marathon * 100.0)
+        },
+    }
+}
+
+fn format_server_value(stat_type: &str, stat_name: &str, value: f64) -> String {
+    match is_distance_stat(stat_type, stat_name) {
+        true => format!("{} km", ((value / CM_PER_KM).round() as u64).to_formatted_string(&Locale::en)),
+        false => format_stat_value(stat_name, value),
+    }
+}
+
+pub async fn get_server_stats(args: &ServerStatsCommandArgs) -> BotResult<ServerStats> {
+    // Every player is counted, even the ones hidden from the leaderboards, except
+    // the players at 0 who would lower the mean and median
+    let values = fetch_unsorted_ranking(args.stat_type.as_str(), args.stat_name.as_str())
+        .await?
+        .into_iter()
+        .map(|s| s.value)
+        .filter(|&value| value != 0.0)
+        .collect::<Vec<f64>>();
+
+    summarize(&values).ok_or_else(|| BotError::Error(format!(
+        "Error: Nobody has any {} yet",
+        make_stat_title(&mut args.stat_type.clone(), &mut args.stat_name.clone())
+    )))
+}
+
+pub fn create_server_stats_embed<'a>(
+    args: &ServerStatsCommandArgs,
+    stats: ServerStats,
+    embed: &'a mut CreateEmbed,
+) -> &'a mut CreateEmbed {
+    let stat_title = make_stat_title(&mut args.stat_type.clone(), &mut args.stat_name.clone());
+    let value = |value: f64| format_server_value(&args.stat_type, &args.stat_name, value);
+
+    embed
+        .title(format!("{} on the server", stat_title))
+        .color((200, 255, 0))
+        .field("Total", value(stats.total), true)
+        .field("Mean", value(stats.mean), true)
+        .field("Median", value(stats.median), true)
+        .field("Players", stats.player_count.to_formatted_string(&Locale::en), true)
+        .footer(|f| f.text("Players at 0 aren't counted in the players, mean and median"));
+
+    if is_distance_stat(&args.stat_type, &args.stat_name) {
+        embed.description(distance_comparison(stats.total));
+    }
+
+    embed
+}
+
+pub fn parse_server_stats_args(
+    args: &[ApplicationCommandInteractionDataOption]
+) -> ServerStatsCommandArgs {
+    let find_arg = |name: &str| args
+        .iter()
+        .find(|&x| x.name.as_str() == name)
+        .and_then(|data| data.value.as_ref())
+        .and_then(|value| value.as_str())
+        .map(|value| value.to_string())
+        .unwrap_or_default();
+
+    let (stat_type, stat_name) = resolve_alias(find_arg("stat-type"), find_arg("stat-name"));
+
+    ServerStatsCommandArgs { stat_type, stat_name }
+}
@@ -0,0 +1,172 @@
+use crate::{
+    bot_error::BotResult,
+    privacy::PrivacyList,
+    seasons::load_seasons,
+    stat::Stat,
+    utils::{ now, trim_uuid },
+};
+
+use serde::{ Deserialize, Serialize };
+use std::{
+    collections::HashMap,
+    fs,
+    path::{ Path, PathBuf },
+};
+
+const SNAPSHOTS_DIR: &str = "snapshots";
+// Time between two saved snapshots, in seconds. The leaderboards refresh more often than that
+const SNAPSHOT_INTERVAL: u64 = 60 * 60;
+const DAY: u64 = 60 * 60 * 24;
+// Every snapshot is kept that long, then only the first one of each day
+const FULL_RETENTION: u64 = DAY * 14;
+// Snapshots older than that are deleted, except the baselines of the seasons
+const RETENTION: u64 = DAY * 365;
+
+/// Values of the tracked stats of every player at some point in time, before the options of
+/// the leaderboards and the privacy list, which are applied when the values are shown
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub taken_at: u64,
+    /// Value by trimmed uuid, by stat key (see `stat_key`)
+    pub values: HashMap<String, HashMap<String, f64>>,
+}
+
+impl Snapshot {
+    pub fn new(rankings: &HashMap<String, Vec<Stat>>) -> Snapshot {
+        let values = rankings
+            .iter()
+            .map(|(key, ranking)| (
+                key.clone(),
+                ranking.iter().map(|s| (trim_uuid(&s.uuid), s.value)).collect(),
+            ))
+            .collect();
+
+        Snapshot { taken_at: now(), values }
+    }
+
+    /// Copy of the snapshot without the players hidden from the leaderboards
+    pub fn without_hidden(&self, privacy_list: &PrivacyList) -> Snapshot {
+        let values = self.values
+            .iter()
+            .map(|(key, values)| (
+                key.clone(),
+                values
+                    .iter()
+                    .filter(|(uuid, _)| !privacy_list.is_hidden(uuid))
+                    .map(|(uuid, &value)| (uuid.clone(), value))
+                    .collect(),
+            ))
+            .collect();
+
+        Snapshot { taken_at: self.taken_at, values }
+    }
+
+    /// Value of a player in a stat, players missing from a tracked stat were at 0.
+    /// None if the stat wasn't tracked yet
+    pub fn value(&self, key: &str, uuid: &str) -> Option<f64> {
+        self.values
+            .get(key)
+            .map(|values| values.get(&trim_uuid(uuid)).copied().unwrap_or(0.0))
+    }
+}
+
+pub fn stat_key(stat_type: &str, stat_name: &str) -> String {
+    format!("{}.{}", stat_type, stat_name)
+}
+
+/// Times the saved snapshots were taken at, from their file names, the oldest first
+fn snapshot_times() -> BotResult<Vec<u64>> {
+    if !Path::new(SNAPSHOTS_DIR).exists() {
+        return Ok(vec![]);
+    }
+
+    let mut times = vec![];
+    for entry in fs::read_dir(SNAPSHOTS_DIR)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "ron") {
+            times.extend(path.file_stem().and_then(|stem| stem.to_str()).and_then(|stem| stem.parse::<u64>().ok()));
+        }
+    }
+
+    times.sort_unstable();
+
+    Ok(times)
+}
+
+fn snapshot_path(taken_at: u64) -> PathBuf {
+    Path::new(SNAPSHOTS_DIR).join(format!("{}.ron", taken_at))
+}
+
+pub fn load_snapshot(taken_at: u64) -> BotResult<Snapshot> {
+    Ok(ron::de::from_str(&fs::read_to_string(snapshot_path(taken_at))?)?)
+}
+
+/// The oldest snapshot taken since a time
+pub fn first_snapshot_since(since: u64) -> BotResult<Option<Snapshot>> {
+    snapshot_times()?
+        .into_iter()
+        .find(|&taken_at| taken_at >= since)
+        .map(load_snapshot)
+        .transpose()
+}
+
+/// Every snapshot taken since a time, the oldest first
+pub fn snapshots_since(since: u64) -> BotResult<Vec<Snapshot>> {
+    snapshot_times()?
+        .into_iter()
+        .filter(|&taken_at| taken_at >= since)
+        .map(load_snapshot)
+        .collect()
+}
+
+/// The latest snapshots, the oldest first
+pub fn latest_snapshots(count: usize) -> BotResult<Vec<Snapshot>> {
+    let times = snapshot_times()?;
+
+    times[times.len().saturating_sub(count)..].iter().map(|&taken_at| load_snapshot(taken_at)).collect()
+}
+
+pub fn latest_snapshot() -> BotResult<Option<Snapshot>> {
+    snapshot_times()?.last().map(|&taken_at| load_snapshot(taken_at)).transpose()
+}
+
+/// Save a snapshot if the latest one is old enough, and delete the ones past their retention
+pub fn record_snapshot(snapshot: &Snapshot, latest: Option<&Snapshot>) -> BotResult<()> {
+    if latest.is_some_and(|latest| snapshot.taken_at < latest.taken_at + SNAPSHOT_INTERVAL) {
+        return Ok(());
+    }
+
+    save_snapshot(snapshot)?;
+
+    let baselines = load_seasons()?.iter().map(|season| season.baseline).collect::<Vec<u64>>();
+    for taken_at in snapshots_to_prune(&snapshot_times()?, snapshot.taken_at, &baselines) {
+        fs::remove_file(snapshot_path(taken_at))?;
+    }
+
+    Ok(())
+}
+
+/// Times of the snapshots past their retention, from the times of every snapshot, the oldest first.
+/// The kept ones, like the baselines of the seasons, are never pruned
+pub fn snapshots_to_prune(times: &[u64], now: u64, kept: &[u64]) -> Vec<u64> {
+    let mut previous_day = None;
+
+    times
+        .iter()
+        .copied()
+        .filter(|&taken_at| {
+            let age = now.saturating_sub(taken_at);
+            let first_of_day = previous_day != Some(taken_at / DAY);
+            previous_day = Some(taken_at / DAY);
+
+            !kept.contains(&taken_at) && (age > RETENTION || (age > FULL_RETENTION && !first_of_day))
+        })
+        .collect()
+}
+
+pub fn save_snapshot(snapshot: &Snapshot) -> BotResult<()> {
+    fs::create_dir_all(SNAPSHOTS_DIR)?;
+    fs::write(snapshot_path(snapshot.taken_at), ron::ser::to_string(snapshot)?)?;
+
+    Ok(())
+}
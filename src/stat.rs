@@ -1,8 +1,9 @@
+use crate::aliases::resolve_alias;
 use crate::bot_error::{ BotResult, BotError };
+use crate::derived::{ fetch_derived_stat, DERIVED_STAT_TYPE };
 use crate::utils::*;
 use serde::Deserialize;
-
-use num_format::{ Locale, ToFormattedString };
+use futures::{ stream, StreamExt };
 
 use serenity::{
     builder::CreateEmbed,
@@ -10,7 +11,7 @@ use serenity::{
 };
 
 pub struct StatCommandArgs {
-    pub player: String,
+    pub player: Option<String>,
     pub stat_type: String,
     pub stat_name: String,
 }
@@ -22,7 +23,7 @@ pub struct Stat {
     #[serde(skip_deserializing)]
     pub username: String,
     #[serde(rename = "stat")]
-    pub value: u64,
+    pub value: f64,
 }
 
 pub async fn get_stat<S>(player: S, stat_type: S, stat_name: S) -> BotResult<Stat> 
@@ -35,8 +36,13 @@ pub async fn get_stat<S>(player: S, stat_type: S, stat_name: S) -> BotResult<Sta
         uuid = untrim_uuid(uuid);
     }
 
+    let stat_type = stat_type.into();
+    if stat_type == DERIVED_STAT_TYPE {
+        return fetch_derived_stat(&uuid, &stat_name.into()).await;
+    }
+
     // Transform the stat name and the stat type into coorect minecraft ids
-    let stat_type = name_to_minecraft_id(stat_type.into());
+    let stat_type = name_to_minecraft_id(stat_type);
     let stat_name = name_to_minecraft_id(stat_name.into());
 
     let stat = fetch_stat(uuid, stat_type, stat_name).await?;
@@ -44,6 +50,34 @@ pub async fn get_stat<S>(player: S, stat_type: S, stat_name: S) -> BotResult<Sta
     Ok(stat)
 }
 
+/// Get several stats of a player, only looking up their uuid once.
+/// Also returns the uuid of the player
+pub async fn get_stats(player: &str, stats: &[(&str, &str)]) -> BotResult<(String, Vec<Stat>)> {
+    const CONCURRENT_REQUESTS: usize = 10;
+
+    let uuid = get_uuid_from_username(player).await?;
+
+    let requests = stats
+        .iter()
+        .map(|&(stat_type, stat_name)| (uuid.clone(), stat_type.to_string(), stat_name.to_string()))
+        .collect::<Vec<(String, String, String)>>();
+
+    let stats = stream::iter(requests)
+        .map(|(uuid, stat_type, stat_name)| async move {
+            match stat_type.as_str() {
+                DERIVED_STAT_TYPE => fetch_derived_stat(&uuid, &stat_name).await,
+                _ => fetch_stat(uuid, name_to_minecraft_id(stat_type), name_to_minecraft_id(stat_name)).await,
+            }
+        })
+        .buffered(CONCURRENT_REQUESTS)
+        .collect::<Vec<BotResult<Stat>>>()
+        .await
+        .into_iter()
+        .collect::<BotResult<Vec<Stat>>>()?;
+
+    Ok((uuid, stats))
+}
+
 async fn fetch_stat<S>(uuid: S, stat_type: S, stat_name: S) -> BotResult<Stat>
 where
     S: Into<String>
@@ -68,16 +102,9 @@ where
 }
 
 pub fn parse_stat_args(
-    args: &Vec<ApplicationCommandInteractionDataOption>
+    args: &[ApplicationCommandInteractionDataOption]
 ) -> StatCommandArgs {
     let mut args_iter = args.iter();
-    let player = args_iter 
-        .find(|&x| x.name.as_str() == "player")
-        .unwrap()
-        .value.as_ref()
-        .unwrap()
-        .to_string()
-        .replace("\"", "");
     let stat_type = args_iter
         .find(|&x| x.name.as_str() == "stat-type")
         .unwrap()
@@ -92,31 +119,35 @@ pub fn parse_stat_args(
         .unwrap()
         .to_string()
         .replace("\"", "");
+    let (stat_type, stat_name) = resolve_alias(stat_type, stat_name);
+    // The linked account of the user is used when there is no player
+    let player = args
+        .iter()
+        .find(|&x| x.name.as_str() == "player")
+        .and_then(|data| data.value.as_ref())
+        .and_then(|player| player.as_str())
+        .map(|player| player.to_string());
 
     StatCommandArgs { player, stat_type, stat_name }
 }
 
-pub fn create_stat_embed<'a, S>(
-    stat: u64,
+pub fn create_stat_embed<S>(
+    stat: f64,
     player: S,
     uuid: S,
     stat_type: S,
     stat_name: S,
-    embed: &'a mut CreateEmbed
-) -> &'a mut CreateEmbed
+    embed: &mut CreateEmbed
+) -> &mut CreateEmbed
 where
     S: Into<String> + Clone
 {
-    embed.title(&player.into());
+    embed.title(player.into());
     embed.thumbnail(format!("https://crafatar.com/avatars/{}", uuid.into()));
 
     let field_name = make_stat_title(&mut stat_type.into(), &mut stat_name.clone().into());
 
-    embed.field(field_name, if stat_name.into() == "play time" {
-        minecraft_ticks_to_formatted_time(stat)
-    } else {
-        stat.to_formatted_string(&Locale::en)
-    }, false);
+    embed.field(field_name, format_stat_value(&stat_name.into(), stat), false);
 
 
     embed.color((200, 255, 0));
@@ -0,0 +1,290 @@
+use crate::{
+    aliases::resolve_alias,
+    bot_error::{ BotResult, BotError },
+    leaderboard::competition_ranks,
+    links::load_account_links,
+    scheduled_leaderboards::load_leaderboards,
+    snapshots::stat_key,
+    stat::Stat,
+    utils::*,
+};
+
+use serenity::{
+    http::{ client::Http, CacheHttp },
+    model::{ id::UserId, interactions::ApplicationCommandInteractionDataOption },
+};
+
+use serde::{ Deserialize, Serialize };
+use std::{ collections::HashMap, fs, path::Path };
+
+const SUBSCRIPTIONS_FILE: &str = "subscriptions.ron";
+const MAX_SUBSCRIPTIONS_PER_USER: usize = 10;
+// Minimum time between two DMs to the same user, in seconds. Notifications in between are dropped
+const DM_COOLDOWN: u64 = 60 * 30;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Subscriptions {
+    #[serde(default)]
+    pub subscriptions: Vec<Subscription>,
+    /// Time of the last DM sent to each user
+    #[serde(default)]
+    pub last_sent: HashMap<u64, u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subscription {
+    pub user_id: u64,
+    pub stat_type: String,
+    pub stat_name: String,
+    /// Also notify when entering the top N
+    pub top: Option<usize>,
+    /// Rank of the linked account of the user at the previous refresh
+    #[serde(default)]
+    pub last_rank: Option<usize>,
+}
+
+pub enum NotificationKind {
+    /// By the uuid of the player now right above
+    Overtaken(String),
+    EnteredTop(usize),
+}
+
+pub struct Notification {
+    pub user_id: u64,
+    pub stat_type: String,
+    pub stat_name: String,
+    pub rank: usize,
+    pub kind: NotificationKind,
+}
+
+pub enum SubscriptionAction {
+    Subscribe,
+    Unsubscribe,
+}
+
+pub struct SubscriptionCommandArgs {
+    pub action: SubscriptionAction,
+    pub stat_type: Option<String>,
+    pub stat_name: Option<String>,
+    pub top: Option<usize>,
+}
+
+pub fn load_subscriptions() -> BotResult<Subscriptions> {
+    if !Path::new(SUBSCRIPTIONS_FILE).exists() {
+        return Ok(Subscriptions::default());
+    }
+
+    Ok(ron::de::from_str(&fs::read_to_string(SUBSCRIPTIONS_FILE)?)?)
+}
+
+fn save_subscriptions(subscriptions: &Subscriptions) -> BotResult<()> {
+    fs::write(SUBSCRIPTIONS_FILE, ron::ser::to_string(subscriptions)?)?;
+
+    Ok(())
+}
+
+/// Subscribe to or unsubscribe from a stat, and get the message to answer with.
+/// Only the stats of the leaderboards channel can be subscribed to, since they're the ones ranked each refresh
+pub fn run_subscription_command(args: &SubscriptionCommandArgs, user_id: u64) -> BotResult<String> {
+    let mut subscriptions = load_subscriptions()?;
+
+    let answer = match (&args.action, args.stat_type.as_ref().zip(args.stat_name.as_ref())) {
+        (SubscriptionAction::Subscribe, Some((stat_type, stat_name))) => {
+            if !load_account_links()?.linked.contains_key(&user_id) {
+                return Err(BotError::Error(
+                    "Error: Link your account with `/link` first, to know who to compare the others to".to_string()
+                ));
+            }
+
+            let stat_title = make_stat_title(&mut stat_type.clone(), &mut stat_name.clone());
+            let is_ranked = load_leaderboards()?
+                .iter()
+                .flat_map(|m| m.leaderboards.iter())
+                .any(|l| &l.stat_type == stat_type && &l.stat_name == stat_name);
+            if !is_ranked {
+                return Err(BotError::Error(format!(
+                    "Error: Only the stats of the leaderboards channel can be subscribed to, {} isn't one of them",
+                    stat_title
+                )));
+            }
+
+            subscriptions.subscriptions.retain(|s|
+                !(s.user_id == user_id && &s.stat_type == stat_type && &s.stat_name == stat_name)
+            );
+            if subscriptions.subscriptions.iter().filter(|s| s.user_id == user_id).count() >= MAX_SUBSCRIPTIONS_PER_USER {
+                return Err(BotError::Error(format!(
+                    "Error: You can't subscribe to more than {} stats", MAX_SUBSCRIPTIONS_PER_USER
+                )));
+            }
+
+            subscriptions.subscriptions.push(Subscription {
+                user_id,
+                stat_type: stat_type.clone(),
+                stat_name: stat_name.clone(),
+                top: args.top,
+                last_rank: None,
+            });
+            format!("You'll get a DM when someone passes you in {}", stat_title)
+        },
+        (SubscriptionAction::Subscribe, None) => return Err(BotError::Error(
+            "Error: Give the type and the name of the stat to subscribe to".to_string()
+        )),
+        (SubscriptionAction::Unsubscribe, Some((stat_type, stat_name))) => {
+            subscriptions.subscriptions.retain(|s|
+                !(s.user_id == user_id && &s.stat_type == stat_type && &s.stat_name == stat_name)
+            );
+            format!(
+                "You won't get DMs about {} anymore",
+                make_stat_title(&mut stat_type.clone(), &mut stat_name.clone())
+            )
+        },
+        (SubscriptionAction::Unsubscribe, None) => {
+            subscriptions.subscriptions.retain(|s| s.user_id != user_id);
+            "You won't get DMs about any stat anymore".to_string()
+        },
+    };
+
+    save_subscriptions(&subscriptions)?;
+
+    Ok(answer)
+}
+
+/// Compare the ranks of the subscribers to the previous refresh. Rankings missing from
+/// this refresh leave their subscriptions untouched
+pub fn detect_notifications(
+    subscriptions: &mut Subscriptions,
+    rankings: &HashMap<String, Vec<Stat>>,
+    linked: &HashMap<u64, String>,
+) -> Vec<Notification> {
+    let mut notifications = vec![];
+
+    for subscription in subscriptions.subscriptions.iter_mut() {
+        let (ranking, uuid) = match (
+            rankings.get(&stat_key(&subscription.stat_type, &subscription.stat_name)),
+            linked.get(&subscription.user_id),
+        ) {
+            (Some(ranking), Some(uuid)) => (ranking, uuid),
+            _ => continue,
+        };
+
+        let ranks = competition_ranks(ranking);
+        let index = ranking.iter().position(|s| &trim_uuid(&s.uuid) == uuid);
+        let rank = index.map(|index| ranks[index]);
+        let last_rank = std::mem::replace(&mut subscription.last_rank, rank);
+
+        let (rank, index, last_rank) = match (rank, index, last_rank) {
+            (Some(rank), Some(index), Some(last_rank)) => (rank, index, last_rank),
+            _ => continue,
+        };
+
+        let kind = if rank > last_rank && index > 0 {
+            NotificationKind::Overtaken(trim_uuid(&ranking[index - 1].uuid))
+        } else if let Some(top) = subscription.top.filter(|&top| rank <= top && last_rank > top) {
+            NotificationKind::EnteredTop(top)
+        } else {
+            continue;
+        };
+
+        notifications.push(Notification {
+            user_id: subscription.user_id,
+            stat_type: subscription.stat_type.clone(),
+            stat_name: subscription.stat_name.clone(),
+            rank,
+            kind,
+        });
+    }
+
+    notifications
+}
+
+/// DM the subscribers who were passed or entered the top since the previous refresh
+pub async fn update_subscriptions(
+    http: impl AsRef<Http> + CacheHttp,
+    rankings: &HashMap<String, Vec<Stat>>,
+) -> BotResult<()> {
+    let mut subscriptions = load_subscriptions()?;
+    if subscriptions.subscriptions.is_empty() {
+        return Ok(());
+    }
+
+    let links = load_account_links()?;
+    let notifications = detect_notifications(&mut subscriptions, rankings, &links.linked);
+
+    // Every username shown is looked up at once, the failed lookups skip their notification
+    let overtakers = notifications
+        .iter()
+        .filter_map(|notification| match notification.kind {
+            NotificationKind::Overtaken(ref uuid) => Some(uuid.clone()),
+            _ => None,
+        })
+        .collect::<Vec<String>>();
+    let names = overtakers
+        .iter()
+        .cloned()
+        .zip(lookup_usernames_from_uuids(overtakers.iter().map(|uuid| untrim_uuid(uuid.clone())).collect()).await)
+        .collect::<HashMap<String, BotResult<String>>>();
+
+    for notification in notifications {
+        let last_sent = subscriptions.last_sent.get(&notification.user_id).copied().unwrap_or(0);
+        if now() < last_sent + DM_COOLDOWN {
+            continue;
+        }
+
+        let stat_title = make_stat_title(&mut notification.stat_type.clone(), &mut notification.stat_name.clone());
+        let content = match notification.kind {
+            NotificationKind::Overtaken(ref uuid) => match names.get(uuid) {
+                Some(Ok(name)) => format!("{} passed you in {}, you're now #{}", name, stat_title, notification.rank),
+                Some(Err(e)) => {
+                    println!("Cannot look up the username of {}: {:?}", uuid, e);
+                    continue;
+                },
+                None => continue,
+            },
+            NotificationKind::EnteredTop(top) => format!(
+                "You entered the top {} in {}, you're now #{}", top, stat_title, notification.rank
+            ),
+        };
+
+        let sent = match UserId(notification.user_id).create_dm_channel(&http).await {
+            Ok(channel) => channel.say(&http, content).await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+
+        match sent {
+            Ok(()) => { subscriptions.last_sent.insert(notification.user_id, now()); },
+            Err(e) => println!("Cannot DM {}: {:?}", notification.user_id, e),
+        }
+    }
+
+    save_subscriptions(&subscriptions)?;
+
+    Ok(())
+}
+
+pub fn parse_subscription_args(
+    action: SubscriptionAction,
+    args: &[ApplicationCommandInteractionDataOption]
+) -> SubscriptionCommandArgs {
+    let find_arg = |name: &str| args
+        .iter()
+        .find(|&x| x.name.as_str() == name)
+        .and_then(|data| data.value.as_ref())
+        .and_then(|value| value.as_str())
+        .map(|value| value.to_string());
+
+    let (stat_type, stat_name) = match (find_arg("stat-type"), find_arg("stat-name")) {
+        (Some(stat_type), Some(stat_name)) => {
+            let (stat_type, stat_name) = resolve_alias(stat_type, stat_name);
+            (Some(stat_type), Some(stat_name))
+        },
+        (stat_type, stat_name) => (stat_type, stat_name),
+    };
+    let top = args
+        .iter()
+        .find(|&x| x.name.as_str() == "top")
+        .and_then(|data| data.value.as_ref())
+        .and_then(|top| top.as_u64())
+        .map(|top| top as usize);
+
+    SubscriptionCommandArgs { action, stat_type, stat_name, top }
+}
@@ -0,0 +1,188 @@
+use crate::{
+    bot_error::BotResult,
+    catalog::stat_names,
+    leaderboard::{ RankingOptions, SortOrder },
+};
+
+use serenity::{
+    builder::CreateComponents,
+    model::interactions::ButtonStyle,
+};
+
+use std::{ collections::HashMap, fs, path::Path };
+
+const KNOWN_PLAYERS_FILE: &str = "known_players.ron";
+// Discord doesn't allow more buttons than that in a row
+const MAX_SUGGESTIONS: usize = 5;
+// Discord rejects the whole message when a custom id is longer than that
+const MAX_CUSTOM_ID_LENGTH: usize = 100;
+
+const STAT_SUGGESTION_PREFIX: &str = "stat-suggestion";
+const LEADERBOARD_SUGGESTION_PREFIX: &str = "leaderboard-suggestion";
+
+/// A command to run again with a suggested correction, from the custom id of its button
+pub enum Suggestion {
+    Stat { player: String, stat_type: String, stat_name: String },
+    Leaderboard {
+        stat_type: String,
+        stat_name: String,
+        limit: Option<i64>,
+        player: Option<String>,
+        options: RankingOptions,
+        season: Option<String>,
+    },
+}
+
+pub fn levenshtein_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut previous = (0..=b.len()).collect::<Vec<usize>>();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + if a_char == *b_char { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Get the candidates closest to the input, the closest first.
+/// Candidates too different from the input aren't suggested
+pub fn suggest(input: &str, candidates: &[String]) -> Vec<String> {
+    let input = input.to_lowercase();
+    let max_distance = std::cmp::max(2, input.chars().count() / 3);
+
+    let mut suggestions = candidates
+        .iter()
+        .map(|c| (levenshtein_distance(&input, &c.to_lowercase()), c))
+        .filter(|&(distance, _)| distance > 0 && distance <= max_distance)
+        .collect::<Vec<(usize, &String)>>();
+
+    suggestions.sort_by_key(|&(distance, _)| distance);
+    suggestions.dedup_by(|a, b| a.1 == b.1);
+
+    suggestions
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, c)| c.clone())
+        .collect()
+}
+
+pub fn stat_suggestions(stat_type: &str, stat_name: &str) -> Vec<String> {
+    let names = stat_names(stat_type);
+
+    if names.iter().any(|name| name == stat_name) {
+        return vec![];
+    }
+
+    suggest(stat_name, &names)
+}
+
+pub fn player_suggestions(player: &str) -> Vec<String> {
+    let players = load_known_players()
+        .map(|players| players.into_values().collect::<Vec<String>>())
+        .unwrap_or_default();
+
+    suggest(player, &players)
+}
+
+/// Usernames of the players seen on the leaderboards, by uuid
+pub fn load_known_players() -> BotResult<HashMap<String, String>> {
+    if !Path::new(KNOWN_PLAYERS_FILE).exists() {
+        return Ok(HashMap::new());
+    }
+
+    Ok(ron::de::from_str(&fs::read_to_string(KNOWN_PLAYERS_FILE)?)?)
+}
+
+/// Remember the usernames of players by uuid, only give it usernames that were actually looked up
+pub fn remember_players(found: &[(String, String)]) -> BotResult<()> {
+    let mut players = load_known_players()?;
+
+    let mut changed = false;
+    for (uuid, username) in found.iter().filter(|(_, username)| !username.is_empty()) {
+        if players.get(uuid) != Some(username) {
+            players.insert(uuid.clone(), username.clone());
+            changed = true;
+        }
+    }
+
+    if changed {
+        fs::write(KNOWN_PLAYERS_FILE, ron::ser::to_string(&players)?)?;
+    }
+
+    Ok(())
+}
+
+/// Get the label and the custom id of the buttons of the suggestions.
+/// The arguments of the command are in the custom id, a suggestion that doesn't fit isn't shown
+pub fn suggestion_buttons(suggestions: Vec<(String, Suggestion)>) -> Vec<(String, String)> {
+    suggestions
+        .into_iter()
+        .map(|(label, suggestion)| (label, suggestion_custom_id(suggestion)))
+        .filter(|(_, custom_id)| custom_id.chars().count() <= MAX_CUSTOM_ID_LENGTH)
+        .take(MAX_SUGGESTIONS)
+        .collect()
+}
+
+pub fn create_suggestion_buttons(
+    components: &mut CreateComponents,
+    buttons: Vec<(String, String)>,
+) -> &mut CreateComponents {
+    components.create_action_row(|row| {
+        for (label, custom_id) in buttons {
+            row.create_button(|button| {
+                button
+                    .custom_id(custom_id)
+                    .label(label)
+                    .style(ButtonStyle::Primary)
+            });
+        }
+        row
+    })
+}
+
+fn suggestion_custom_id(suggestion: Suggestion) -> String {
+    match suggestion {
+        Suggestion::Stat { player, stat_type, stat_name } =>
+            format!("{}:{}:{}:{}", STAT_SUGGESTION_PREFIX, player, stat_type, stat_name),
+        Suggestion::Leaderboard { stat_type, stat_name, limit, player, options, season } => format!(
+            "{}:{}:{}:{}:{}:{}:{}:{}:{}",
+            LEADERBOARD_SUGGESTION_PREFIX, stat_type, stat_name, limit.unwrap_or(0), player.unwrap_or_default(),
+            (options.order == SortOrder::Ascending) as u8, options.min_play_hours.unwrap_or(0.0),
+            options.include_ties as u8, season.unwrap_or_default()
+        ),
+    }
+}
+
+pub fn parse_suggestion_button(custom_id: &str) -> Option<Suggestion> {
+    let parts = custom_id.split(':').collect::<Vec<&str>>();
+
+    match parts.as_slice() {
+        [STAT_SUGGESTION_PREFIX, player, stat_type, stat_name] => Some(Suggestion::Stat {
+            player: player.to_string(),
+            stat_type: stat_type.to_string(),
+            stat_name: stat_name.to_string(),
+        }),
+        [LEADERBOARD_SUGGESTION_PREFIX, stat_type, stat_name, limit, player, ascending, min_play_hours, include_ties, season] =>
+            Some(Suggestion::Leaderboard {
+                stat_type: stat_type.to_string(),
+                stat_name: stat_name.to_string(),
+                limit: limit.parse().ok().filter(|&l| l > 0),
+                player: Some(player.to_string()).filter(|p| !p.is_empty()),
+                options: RankingOptions {
+                    order: if *ascending == "1" { SortOrder::Ascending } else { SortOrder::Descending },
+                    min_play_hours: min_play_hours.parse().ok().filter(|&h| h > 0.0),
+                    include_ties: *include_ties == "1",
+                    ..Default::default()
+                },
+                season: Some(season.to_string()).filter(|s| !s.is_empty()),
+            }),
+        _ => None,
+    }
+}
@@ -17,5 +17,564 @@ async fn user_from_uuid() {
 
 #[test]
 fn getting_longest_len_in_string_vec() {
-    assert_eq!(5, longest_length_in_string_vec(&vec!["12".to_string(), "123".to_string(), "01234".to_string(), "123".to_string()]));
+    assert_eq!(5, longest_length_in_string_vec(&["12".to_string(), "123".to_string(), "01234".to_string(), "123".to_string()]));
+}
+
+#[test]
+fn info_message_rendering() {
+    use crate::{
+        info_message::render_info_message,
+        scheduled_leaderboards::{ Leaderboard, LeaderboardMessage },
+    };
+
+    let leaderboards = vec![LeaderboardMessage {
+        message_id: 0,
+        leaderboards: vec![Leaderboard {
+            stat_type: "mined".to_string(),
+            stat_name: "diamond ore".to_string(),
+            limit: None,
+            expression: None,
+            options: Default::default(),
+            announce: false,
+            sparkline: None,
+        }],
+    }];
+
+    assert_eq!(
+        render_info_message("{refresh_interval}|{stats}|{season}|{last_restart}", &leaderboards, "Season 4", 1626000000),
+        "5 minutes|• Diamond ores mined|Season 4|<t:1626000000:f>"
+    );
+}
+
+#[test]
+fn leaderboards_config_loading() {
+    assert!(crate::scheduled_leaderboards::load_leaderboards_from(concat!(env!("CARGO_MANIFEST_DIR"), "/leaderboards.ron")).is_ok());
+}
+
+#[test]
+fn leaderboard_message_splitting() {
+    use crate::scheduled_leaderboards::{ embed_length, split_embeds };
+    use serde_json::json;
+
+    let embed = |length: usize| json!({ "title": "é".repeat(10), "fields": [{ "name": "", "value": "x".repeat(length - 10) }] });
+    assert_eq!(embed_length(&embed(2500)), 2500);
+
+    let sizes = |embeds: Vec<serde_json::Value>| split_embeds(embeds).iter().map(|m| m.len()).collect::<Vec<usize>>();
+    assert_eq!(sizes(vec![embed(2500), embed(2500), embed(2500)]), vec![2, 1]);
+    assert_eq!(sizes((0..12).map(|_| embed(100)).collect()), vec![10, 2]);
+    assert_eq!(sizes(vec![]), Vec::<usize>::new());
+}
+
+#[test]
+fn snapshot_pruning() {
+    use crate::snapshots::snapshots_to_prune;
+
+    const HOUR: u64 = 60 * 60;
+    const DAY: u64 = HOUR * 24;
+    let now = DAY * 1000;
+
+    // Two a day for the past 400 days
+    let times = (1..=400).rev().flat_map(|days| vec![now - days * DAY, now - days * DAY + HOUR]).collect::<Vec<u64>>();
+    let pruned = snapshots_to_prune(&times, now, &[now - 390 * DAY]);
+
+    assert!(!pruned.contains(&(now - 390 * DAY)));
+    assert!(pruned.contains(&(now - 380 * DAY)));
+    assert!(!pruned.contains(&(now - 100 * DAY)) && pruned.contains(&(now - 100 * DAY + HOUR)));
+    assert!(!pruned.iter().any(|&taken_at| taken_at > now - 14 * DAY));
+}
+
+#[test]
+fn unused_leaderboard_messages_cleanup() {
+    use crate::scheduled_leaderboards::{ unused_leaderboard_messages, LeaderboardMessage, LeaderboardMessagesState };
+
+    let message = |message_id: u64| LeaderboardMessage { message_id, leaderboards: vec![] };
+    let state = LeaderboardMessagesState {
+        configured: vec![1, 2, 3],
+        overflow: vec![(1, vec![10]), (3, vec![30, 31])].into_iter().collect(),
+    };
+
+    // Only the recorded messages the leaderboards stopped using are deleted
+    let mut unused = unused_leaderboard_messages(&state, &[message(1), message(4)]);
+    unused.sort_unstable();
+    assert_eq!(unused, vec![2, 3, 30, 31]);
+}
+
+#[test]
+fn leaderboard_pages() {
+    use crate::{ leaderboard::LeaderboardPages, stat::Stat };
+
+    let ranking = (0..12).rev().map(|value| Stat {
+        success: true,
+        uuid: value.to_string(),
+        username: String::new(),
+        value: value as f64,
+    }).collect::<Vec<Stat>>();
+
+    let pages = LeaderboardPages::from_ranking("mined", "stone", ranking, Some(5), false);
+    assert_eq!(pages.page_count(), 3);
+    let (ranks, stats) = pages.page_stats(2);
+    assert_eq!(ranks, vec![11, 12]);
+    assert_eq!(stats.iter().map(|s| s.value).collect::<Vec<f64>>(), vec![1.0, 0.0]);
+}
+
+#[test]
+fn leaderboard_pages_with_ties() {
+    use crate::{ leaderboard::LeaderboardPages, stat::Stat };
+
+    let ranking = [9.0, 8.0, 7.0, 6.0, 6.0, 6.0, 5.0, 4.0].iter().enumerate().map(|(i, &value)| Stat {
+        success: true,
+        uuid: i.to_string(),
+        username: String::new(),
+        value,
+    }).collect::<Vec<Stat>>();
+
+    // The players tied with the last one of a page are on that page too
+    let pages = LeaderboardPages::from_ranking("mined", "stone", ranking.clone(), Some(4), true);
+    assert_eq!(pages.page_count(), 2);
+    assert_eq!(pages.page_stats(0).0, vec![1, 2, 3, 4, 4, 4]);
+    assert_eq!(pages.page_stats(1).0, vec![7, 8]);
+
+    let pages = LeaderboardPages::from_ranking("mined", "stone", ranking, Some(4), false);
+    assert_eq!(pages.page_stats(1).0, vec![4, 4, 7, 8]);
+}
+
+#[test]
+fn around_player_window() {
+    use crate::leaderboard::around_range;
+
+    assert_eq!(around_range(10, 20), 7..14);
+    assert_eq!(around_range(1, 20), 0..5);
+    assert_eq!(around_range(18, 20), 15..20);
+    assert_eq!(around_range(0, 1), 0..1);
+}
+
+#[test]
+fn player_ranks() {
+    use crate::{ rank::player_rank, stat::Stat };
+
+    let ranking = [("a", 12.0), ("b", 7.0), ("c", 3.0)].iter().map(|&(uuid, value)| Stat {
+        success: true,
+        uuid: uuid.to_string(),
+        username: String::new(),
+        value,
+    }).collect::<Vec<Stat>>();
+
+    let rank = player_rank("mined".to_string(), "stone".to_string(), &ranking, "b").unwrap();
+    assert_eq!((rank.rank, rank.player_count, rank.value), (2, 3, 7.0));
+    assert!(player_rank("mined".to_string(), "stone".to_string(), &ranking, "d").is_none());
+}
+
+#[test]
+fn comparison_gaps() {
+    use crate::{ compare::gaps_to_leader, stat::Stat };
+
+    let stats = [120.0, 300.0, 300.0, 0.0].iter().map(|&value| Stat {
+        success: true,
+        uuid: String::new(),
+        username: String::new(),
+        value,
+    }).collect::<Vec<Stat>>();
+
+    assert_eq!(gaps_to_leader(&stats), vec![180.0, 0.0, 0.0, 300.0]);
+}
+
+#[test]
+fn profile_top_values() {
+    use crate::profile::top_values;
+
+    let names = ["stone", "dirt", "sand", "gravel", "tuff", "granite", "diorite"];
+    let top = top_values(&names, vec![50, 0, 700, 50, 3, 9, 1]);
+
+    assert_eq!(top, vec![
+        ("sand".to_string(), 700),
+        ("stone".to_string(), 50),
+        ("gravel".to_string(), 50),
+        ("granite".to_string(), 9),
+        ("tuff".to_string(), 3),
+    ]);
+}
+
+#[test]
+fn derived_stats_config_loading() {
+    assert!(crate::derived::load_derived_stats_from(concat!(env!("CARGO_MANIFEST_DIR"), "/derived_stats.ron")).is_ok());
+}
+
+#[test]
+fn expression_evaluation() {
+    use crate::expression::parse_expression;
+    use std::collections::HashMap;
+
+    let expression = parse_expression("mined.diamond_ore*10 + killed.ender_dragon*500 - custom.deaths*50").unwrap();
+
+    let mut values = HashMap::new();
+    values.insert(("mined".to_string(), "diamond ore".to_string()), 12.0);
+    values.insert(("custom".to_string(), "deaths".to_string()), 3.0);
+
+    assert_eq!(expression.stats().len(), 3);
+    assert_eq!(expression.evaluate(&values), Some(-30.0));
+    assert_eq!(parse_expression("-(2 + 4) / 4").unwrap().evaluate(&HashMap::new()), Some(-1.5));
+    assert!(parse_expression("mined.diamond_ore *").is_err());
+    assert!(parse_expression("stolen.diamond").is_err());
+}
+
+#[test]
+fn stat_alias_resolution() {
+    use crate::aliases::{ find_alias, load_aliases_from };
+
+    let aliases = load_aliases_from(concat!(env!("CARGO_MANIFEST_DIR"), "/stat_aliases.ron")).unwrap();
+
+    assert_eq!(find_alias(&aliases, "mined".to_string(), "Diamonds".to_string()), ("derived".to_string(), "diamonds".to_string()));
+    assert_eq!(find_alias(&aliases, "killed by".to_string(), "zombies".to_string()), ("killed by".to_string(), "zombie".to_string()));
+    assert_eq!(find_alias(&aliases, "mined".to_string(), "iron_ore".to_string()), ("mined".to_string(), "iron ore".to_string()));
+}
+
+#[test]
+fn stat_name_suggestions() {
+    use crate::suggestions::{ levenshtein_distance, stat_suggestions };
+
+    assert_eq!(levenshtein_distance("Elzapet", "Elzapat"), 1);
+    assert_eq!(levenshtein_distance("", "abc"), 3);
+    assert_eq!(stat_suggestions("mined", "diamnd ore").first().map(|s| s.as_str()), Some("diamond ore"));
+    assert!(stat_suggestions("mined", "diamond ore").is_empty());
+}
+
+#[test]
+fn suggestion_button_ids() {
+    use crate::suggestions::{ parse_suggestion_button, suggestion_buttons, Suggestion };
+
+    let suggestion = |stat_name: &str| (stat_name.to_string(), Suggestion::Leaderboard {
+        stat_type: "mined".to_string(),
+        stat_name: stat_name.to_string(),
+        limit: Some(10),
+        player: Some("Elzapat".to_string()),
+        options: Default::default(),
+        season: None,
+    });
+
+    let buttons = suggestion_buttons(vec![suggestion("diamond ore"), suggestion(&"deepslate ".repeat(10))]);
+    assert_eq!(buttons.len(), 1);
+    assert!(matches!(
+        parse_suggestion_button(&buttons[0].1),
+        Some(Suggestion::Leaderboard { ref stat_name, limit: Some(10), .. }) if stat_name == "diamond ore"
+    ));
+}
+
+#[test]
+fn ranking_sort_order() {
+    use crate::{ leaderboard::{ sort_ranking, SortOrder }, stat::Stat };
+
+    let mut stats = [3.0, 1.0, 2.0].iter().map(|&value| Stat {
+        success: true,
+        uuid: String::new(),
+        username: String::new(),
+        value,
+    }).collect::<Vec<Stat>>();
+
+    sort_ranking(&mut stats, SortOrder::Ascending);
+    assert_eq!(stats.iter().map(|s| s.value).collect::<Vec<f64>>(), vec![1.0, 2.0, 3.0]);
+    sort_ranking(&mut stats, SortOrder::Descending);
+    assert_eq!(stats.iter().map(|s| s.value).collect::<Vec<f64>>(), vec![3.0, 2.0, 1.0]);
+}
+
+#[test]
+fn competition_ranking() {
+    use crate::{ leaderboard::{ competition_ranks, sort_ranking, SortOrder }, stat::Stat };
+
+    let mut stats = [("c", 5.0), ("b", 3.0), ("a", 5.0), ("d", 1.0)].iter().map(|&(uuid, value)| Stat {
+        success: true,
+        uuid: uuid.to_string(),
+        username: String::new(),
+        value,
+    }).collect::<Vec<Stat>>();
+
+    sort_ranking(&mut stats, SortOrder::Descending);
+    assert_eq!(stats.iter().map(|s| s.uuid.as_str()).collect::<Vec<&str>>(), vec!["a", "c", "b", "d"]);
+    assert_eq!(competition_ranks(&stats), vec![1, 1, 3, 4]);
+}
+
+#[test]
+fn privacy_list_hiding() {
+    use crate::privacy::PrivacyList;
+
+    let mut list = PrivacyList::default();
+    list.opted_out.insert("bb1784e458ee40749ae248684656aa59".to_string(), 0);
+
+    assert!(list.is_hidden("bb1784e4-58ee-4074-9ae2-48684656aa59"));
+    assert!(!list.is_hidden("069a79f444e94726a5befca90e38aaf5"));
+}
+
+#[test]
+fn leader_change_detection() {
+    use crate::{
+        announcements::{ detect_announcements, Announcement, AnnouncementKind, AnnouncementsState },
+        scheduled_leaderboards::Leaderboard,
+        stat::Stat,
+    };
+
+    let leaderboard = Leaderboard {
+        stat_type: "mined".to_string(),
+        stat_name: "diamond ore".to_string(),
+        limit: None,
+        expression: None,
+        options: Default::default(),
+        announce: true,
+        sparkline: None,
+    };
+    let stat = |uuid: &str, value: f64| Stat {
+        success: true,
+        uuid: uuid.to_string(),
+        username: uuid.to_string(),
+        value,
+    };
+    let kinds = |announcements: Vec<Announcement>| announcements
+        .into_iter()
+        .map(|a| match a.kind {
+            AnnouncementKind::NewLeader => format!("leader {}", a.leader.uuid),
+            AnnouncementKind::Record => format!("record {} {}", a.leader.uuid, a.leader.value),
+        })
+        .collect::<Vec<String>>();
+    let mut state = AnnouncementsState::default();
+    let mut refresh = |values: Vec<Stat>| kinds(detect_announcements(&leaderboard, &values, &values, &values, &mut state));
+
+    // The season starts at these values
+    assert!(refresh(vec![stat("a", 100.0), stat("b", 20.0)]).is_empty());
+    assert!(refresh(vec![stat("a", 110.0), stat("b", 25.0)]).is_empty());
+    assert!(refresh(vec![stat("a", 120.0), stat("b", 30.0)]).is_empty());
+    // b gained the most this season, without leading
+    assert_eq!(refresh(vec![stat("a", 121.0), stat("b", 60.0)]), vec!["record b 40"]);
+    assert_eq!(refresh(vec![stat("b", 130.0), stat("a", 122.0)]), vec!["leader b"]);
+    assert_eq!(refresh(vec![stat("a", 140.0), stat("b", 130.0)]), vec!["leader a"]);
+}
+
+#[test]
+fn entered_link_codes() {
+    use crate::links::latest_entered_code;
+
+    let entered_codes = "\
+bb1784e4-58ee-4074-9ae2-48684656aa59 123456
+069a79f4-44e9-4726-a5be-fca90e38aaf5 654321
+bb1784e4-58ee-4074-9ae2-48684656aa59 111111
+malformed line
+";
+
+    assert_eq!(latest_entered_code(entered_codes, "bb1784e458ee40749ae248684656aa59"), Some("111111".to_string()));
+    assert_eq!(latest_entered_code(entered_codes, "069a79f444e94726a5befca90e38aaf5"), Some("654321".to_string()));
+    assert_eq!(latest_entered_code(entered_codes, "853c80ef3c3749fdaa49938b674adae6"), None);
+}
+
+#[test]
+fn milestone_crossing() {
+    use crate::{ milestones::{ detect_milestones, Milestone }, snapshots::Snapshot, stat::Stat };
+    use std::collections::HashMap;
+
+    let milestones = vec![Milestone {
+        stat_type: "custom".to_string(),
+        stat_name: "mob kills".to_string(),
+        value: 100.0,
+        role_id: None,
+    }];
+    let ranking = |values: &[(&str, f64)]| {
+        let mut rankings = HashMap::new();
+        rankings.insert("custom.mob kills".to_string(), values.iter().map(|&(uuid, value)| Stat {
+            success: true,
+            uuid: uuid.to_string(),
+            username: String::new(),
+            value,
+        }).collect::<Vec<Stat>>());
+        rankings
+    };
+    let mut reached = HashMap::new();
+
+    // a was already above the milestone, b crosses it, c is new and starts above it
+    let previous = Snapshot::new(&ranking(&[("a", 150.0), ("b", 90.0)]));
+    let crossed = detect_milestones(&milestones, &ranking(&[("a", 160.0), ("b", 110.0), ("c", 120.0)]), &previous, &mut reached);
+    assert_eq!(crossed.iter().map(|(_, s)| s.uuid.as_str()).collect::<Vec<&str>>(), vec!["b", "c"]);
+
+    // Never announced twice
+    assert!(detect_milestones(&milestones, &ranking(&[("b", 110.0)]), &previous, &mut reached).is_empty());
+}
+
+#[test]
+fn overtake_notifications() {
+    use crate::{
+        stat::Stat,
+        subscriptions::{ detect_notifications, NotificationKind, Subscription, Subscriptions },
+    };
+    use std::collections::HashMap;
+
+    let ranking = |values: &[(&str, f64)]| {
+        let mut rankings = HashMap::new();
+        rankings.insert("custom.jump".to_string(), values.iter().map(|&(uuid, value)| Stat {
+            success: true,
+            uuid: uuid.to_string(),
+            username: String::new(),
+            value,
+        }).collect::<Vec<Stat>>());
+        rankings
+    };
+    let mut linked = HashMap::new();
+    linked.insert(1, "me".to_string());
+    let mut subscriptions = Subscriptions::default();
+    subscriptions.subscriptions.push(Subscription {
+        user_id: 1,
+        stat_type: "custom".to_string(),
+        stat_name: "jump".to_string(),
+        top: Some(1),
+        last_rank: None,
+    });
+
+    assert!(detect_notifications(&mut subscriptions, &ranking(&[("a", 3.0), ("me", 2.0)]), &linked).is_empty());
+    assert!(matches!(
+        detect_notifications(&mut subscriptions, &ranking(&[("me", 4.0), ("a", 3.0)]), &linked)[0].kind,
+        NotificationKind::EnteredTop(1)
+    ));
+    match detect_notifications(&mut subscriptions, &ranking(&[("b", 5.0), ("me", 4.0), ("a", 3.0)]), &linked)[0].kind {
+        NotificationKind::Overtaken(ref uuid) => assert_eq!(uuid, "b"),
+        _ => panic!("expected an overtake"),
+    }
+}
+
+#[test]
+fn weekly_changes() {
+    use crate::{
+        digest::{ new_players, stat_changes },
+        leaderboard::RankingOptions,
+        snapshots::Snapshot,
+    };
+    use std::collections::HashMap;
+
+    let snapshot = |values: &[(&str, f64)]| {
+        let mut snapshot = Snapshot::default();
+        snapshot.values.insert(
+            "custom.jump".to_string(),
+            values.iter().map(|&(uuid, value)| (uuid.to_string(), value)).collect::<HashMap<String, f64>>()
+        );
+        snapshot
+    };
+    let old = snapshot(&[("a", 10.0), ("b", 5.0)]);
+    let new = snapshot(&[("a", 12.0), ("b", 15.0), ("c", 1.0)]);
+    let options = RankingOptions::default();
+
+    let changes = stat_changes(&old, &new, "custom.jump", &options).unwrap();
+    assert_eq!(changes.iter().map(|c| (c.uuid.as_str(), c.gain)).collect::<Vec<(&str, f64)>>(), vec![("b", 10.0), ("a", 2.0), ("c", 1.0)]);
+    assert_eq!(changes[0].ranks, (Some(2), Some(1)));
+    assert_eq!(changes[2].ranks, (None, Some(3)));
+    assert_eq!(new_players(&old, &new), vec!["c".to_string()]);
+
+    // Ranked with the options of the leaderboard
+    let options = RankingOptions { min_value: Some(11.0), ..Default::default() };
+    let changes = stat_changes(&old, &new, "custom.jump", &options).unwrap();
+    assert_eq!(changes[0].ranks, (None, Some(1)));
+    assert_eq!(changes[1].ranks, (None, Some(2)));
+
+    // Stats tracked since the old snapshot have nothing to compare with
+    assert!(stat_changes(&Snapshot::default(), &new, "custom.jump", &options).unwrap().is_empty());
+    assert!(new_players(&Snapshot::default(), &new).is_empty());
+}
+
+#[test]
+fn season_standings_from_baseline() {
+    use crate::{
+        leaderboard::SortOrder,
+        scheduled_leaderboards::Leaderboard,
+        seasons::season_standings,
+        snapshots::Snapshot,
+        stat::Stat,
+    };
+    use std::collections::HashMap;
+
+    let mut leaderboard = Leaderboard {
+        stat_type: "custom".to_string(),
+        stat_name: "jump".to_string(),
+        limit: None,
+        expression: None,
+        options: Default::default(),
+        announce: false,
+        sparkline: None,
+    };
+    let stat = |uuid: &str, value: f64| Stat { success: true, uuid: uuid.to_string(), username: String::new(), value };
+    let mut rankings = HashMap::new();
+    rankings.insert("custom.jump".to_string(), vec![stat("a", 30.0), stat("b", 25.0), stat("c", 4.0)]);
+
+    let mut baseline = Snapshot::default();
+    baseline.values.insert(
+        "custom.jump".to_string(),
+        vec![("a".to_string(), 28.0), ("b".to_string(), 5.0), ("c".to_string(), 4.0)].into_iter().collect()
+    );
+
+    let standings = season_standings(&[leaderboard.clone()], &rankings, &baseline).unwrap();
+    assert_eq!(standings["custom.jump"], vec![("b".to_string(), 20.0), ("a".to_string(), 2.0)]);
+
+    // Nobody gaining any is the best of an ascending leaderboard
+    leaderboard.options.order = SortOrder::Ascending;
+    let standings = season_standings(&[leaderboard], &rankings, &baseline).unwrap();
+    assert_eq!(standings["custom.jump"][0], ("c".to_string(), 0.0));
+}
+
+#[test]
+fn hall_of_fame_records() {
+    use crate::{
+        hall_of_fame::HallOfFame,
+        leaderboard::{ RankingOptions, SortOrder },
+        scheduled_leaderboards::Leaderboard,
+        stat::Stat,
+    };
+
+    let leaderboard = Leaderboard {
+        stat_type: "custom".to_string(),
+        stat_name: "deaths".to_string(),
+        limit: None,
+        expression: None,
+        options: RankingOptions { order: SortOrder::Ascending, ..Default::default() },
+        announce: false,
+        sparkline: None,
+    };
+    let stat = |uuid: &str, value: f64| Stat { success: true, uuid: uuid.to_string(), username: uuid.to_string(), value };
+
+    let mut hall_of_fame = HallOfFame::default();
+    hall_of_fame.add_season("one", vec![(&leaderboard, stat("a", 3.0))]);
+    hall_of_fame.add_season("two", vec![(&leaderboard, stat("b", 1.0))]);
+    hall_of_fame.add_season("three", vec![(&leaderboard, stat("a", 2.0))]);
+    // Recording a season again after an error doesn't add it twice
+    hall_of_fame.add_season("three", vec![(&leaderboard, stat("a", 2.0))]);
+
+    let history = &hall_of_fame.stats[0];
+    assert_eq!(history.winners.iter().map(|w| w.season.as_str()).collect::<Vec<&str>>(), vec!["one", "two", "three"]);
+    assert_eq!(history.record.as_ref().map(|r| (r.season.as_str(), r.holder.uuid.as_str())), Some(("two", "b")));
+}
+
+#[test]
+fn history_chart_rendering() {
+    use crate::chart::{ render_line_chart, Series, HEIGHT, WIDTH };
+
+    let series = vec![Series { color: (255, 0, 0), points: vec![(0.0, 0.0), (1.0, 10.0), (2.0, 5.0)] }];
+    let png = render_line_chart(&series).unwrap();
+
+    let decoder = png::Decoder::new(png.as_slice());
+    let (info, mut reader) = decoder.read_info().unwrap();
+    assert_eq!((info.width, info.height), (WIDTH, HEIGHT));
+
+    let mut pixels = vec![0; info.buffer_size()];
+    reader.next_frame(&mut pixels).unwrap();
+    assert!(pixels.chunks(3).any(|pixel| pixel == [255, 0, 0]));
+}
+
+#[test]
+fn sparkline_drawing() {
+    assert_eq!(sparkline(&[0.0, 1.0, 7.0, 3.5]), "▁▂█▅");
+    assert_eq!(sparkline(&[2.0, 2.0]), "▁▁");
+    assert_eq!(sparkline(&[]), "");
+}
+
+#[test]
+fn server_totals() {
+    use crate::server_stats::{ distance_comparison, summarize, ServerStats };
+
+    assert_eq!(summarize(&[4.0, 1.0, 10.0, 3.0]), Some(ServerStats { total: 18.0, mean: 4.5, median: 3.5, player_count: 4 }));
+    assert_eq!(summarize(&[2.0, 9.0, 1.0]).map(|s| s.median), Some(2.0));
+    assert_eq!(summarize(&[]), None);
+
+    // 100,000 km, in centimeters
+    assert_eq!(distance_comparison(10_000_000_000.0), "That's 2.5 trips around the Earth");
+    assert_eq!(distance_comparison(2_109_750.0), "That's 50% of a marathon");
 }
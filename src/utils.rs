@@ -1,9 +1,13 @@
 use crate::bot_error::{ BotResult, BotError };
+use crate::derived::DERIVED_STAT_TYPE;
 use serde::Deserialize;
 use futures::{ stream, StreamExt };
+use num_format::{ Locale, ToFormattedString };
+use std::time::{ SystemTime, UNIX_EPOCH };
 
 pub const SERVER_ADDRESS: &str = "http://estillacraft.net";
 pub const LEADERBOARDS_CHANNEL: u64 = 863383101841735701;
+pub const INFO_MESSAGE: u64 = 863385529831260221;
 
 pub fn make_ascii_titlecase(s: &mut str) -> String {
     if let Some(r) = s.get_mut(0..1) {
@@ -13,19 +17,24 @@ pub fn make_ascii_titlecase(s: &mut str) -> String {
     s.to_string()
 }
 
+/// Current unix time, in seconds
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
 pub fn name_to_minecraft_id(name: String) -> String {
     format!("minecraft:{}", name.replace(" ", "_").to_lowercase())
 }
 
-pub fn make_stat_title(mut stat_type: &mut String, mut stat_name: &mut String) -> String {
-    if stat_name.chars().last().unwrap() != 's' && stat_type != "custom" {
-        stat_name.push_str("s");
+pub fn make_stat_title(stat_type: &mut String, stat_name: &mut String) -> String {
+    if !stat_name.ends_with('s') && stat_type != "custom" && stat_type != DERIVED_STAT_TYPE {
+        stat_name.push('s');
     }
 
     match stat_type.as_str() {
-        "custom" => make_ascii_titlecase(&mut stat_name),
-        "killed by" => format!("{} {}", make_ascii_titlecase(&mut stat_type), stat_name),
-        _ => format!("{} {}", make_ascii_titlecase(&mut stat_name), stat_type),
+        "custom" | DERIVED_STAT_TYPE => make_ascii_titlecase(stat_name),
+        "killed by" => format!("{} {}", make_ascii_titlecase(stat_type), stat_name),
+        _ => format!("{} {}", make_ascii_titlecase(stat_name), stat_type),
     }
 }
 
@@ -33,6 +42,11 @@ pub fn untrim_uuid(uuid: String) -> String {
     format!("{}-{}-{}-{}-{}", &uuid[0..8], &uuid[8..12], &uuid[12..16], &uuid[16..20], &uuid[20..32])
 }
 
+/// Remove the hyphens of a uuid, the stats API uses hyphenated uuids but Mojang doesn't
+pub fn trim_uuid(uuid: &str) -> String {
+    uuid.replace("-", "")
+}
+
 pub async fn get_uuid_from_username<S>(username: S) -> BotResult<String>
     where S: Into<String>
 {
@@ -41,13 +55,15 @@ pub async fn get_uuid_from_username<S>(username: S) -> BotResult<String>
         id: String,
     }
 
+    let username = username.into();
+
     // Use the Mojang API to get the UUID of the player
-    let request = format!("https://api.mojang.com/users/profiles/minecraft/{}", username.into());
+    let request = format!("https://api.mojang.com/users/profiles/minecraft/{}", username);
     let response = reqwest::get(request).await?;
 
     // Response is successful but there is no match for the given username
     if response.status().as_u16() == 204 {
-        return Err(BotError::Error("Error: This username doesn't exist".to_string()));
+        return Err(BotError::UnknownPlayer(username));
     }
 
     let player = response.json::<MinecraftPlayer>().await?;
@@ -78,7 +94,8 @@ pub async fn get_username_from_uuid<S>(uuid: S) -> BotResult<String>
     Ok(name_history.last().unwrap().name.clone())
 }
 
-pub async fn get_usernames_from_uuids(uuids: Vec<String>) -> BotResult<Vec<String>> {
+/// Look up the username of each uuid, keeping the failed lookups as errors
+pub async fn lookup_usernames_from_uuids(uuids: Vec<String>) -> Vec<BotResult<String>> {
     const CONCURRENT_REQUESTS: usize = 10;
 
     #[derive(Debug, Deserialize)]
@@ -112,20 +129,24 @@ pub async fn get_usernames_from_uuids(uuids: Vec<String>) -> BotResult<Vec<Strin
         })
         .buffered(CONCURRENT_REQUESTS);
 
-    let names = name_histories
+    name_histories
         .collect::<Vec<_>>()
         .await
-        .iter()
-        .map(|name_history| match name_history {
-            Ok(name_history) => name_history.data.player.username.clone()/*.replace("_", "\\_")*/,
-            Err(e) => e.to_string(),
-        })
-        .collect();
+        .into_iter()
+        .map(|name_history| Ok(name_history?.data.player.username/*.replace("_", "\\_")*/))
+        .collect()
+}
 
-    Ok(names)
+/// Usernames of the uuids, with the error in place of the failed lookups
+pub async fn get_usernames_from_uuids(uuids: Vec<String>) -> BotResult<Vec<String>> {
+    Ok(lookup_usernames_from_uuids(uuids)
+        .await
+        .into_iter()
+        .map(|name| name.unwrap_or_else(|e| e.to_string()))
+        .collect())
 }
 
-pub fn longest_length_in_string_vec(source: &Vec<String>) -> usize {
+pub fn longest_length_in_string_vec(source: &[String]) -> usize {
     source.iter().fold(1, |acc, item| {
         if item.len() > acc {
             item.len()
@@ -141,9 +162,9 @@ pub fn minecraft_ticks_to_formatted_time(ticks: u64) -> String {
     let mut hours = minutes / 60;
     let days = hours / 24;
 
-    seconds = seconds % 60;
-    minutes = minutes % 60;
-    hours = hours % 24;
+    seconds %= 60;
+    minutes %= 60;
+    hours %= 24;
 
     let days_s = if days > 0 { format!("{}d ", days) } else { "".to_string() }; 
     let hours_s = if days > 0 || hours > 0 { format!("{:2}h ", hours) } else { "".to_string() };
@@ -152,3 +173,30 @@ pub fn minecraft_ticks_to_formatted_time(ticks: u64) -> String {
 
     format!("{}{}{}{}", days_s, hours_s, minutes_s, seconds_s)
 }
+
+pub fn format_stat_value(stat_name: &str, value: f64) -> String {
+    if stat_name == "play time" {
+        minecraft_ticks_to_formatted_time(value as u64)
+    } else if value.fract() == 0.0 {
+        (value as i64).to_formatted_string(&Locale::en)
+    } else {
+        // Derived stats like ratios aren't whole numbers
+        format!("{:.2}", value)
+    }
+}
+
+/// Draw values as a line of block characters, scaled from the smallest to the biggest value
+pub fn sparkline(values: &[f64]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let min = values.iter().cloned().fold(f64::MAX, f64::min);
+    let max = values.iter().cloned().fold(f64::MIN, f64::max);
+
+    values
+        .iter()
+        .map(|&value| match max > min {
+            true => BLOCKS[((value - min) / (max - min) * (BLOCKS.len() - 1) as f64).round() as usize],
+            false => BLOCKS[0],
+        })
+        .collect()
+}